@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 
-use aries_model::assignments::Assignment;
+use aries_model::assignments::{Assignment, LabeledValues, SavedAssignment};
 
 #[derive(Debug)]
 struct JobShop {
@@ -51,6 +51,29 @@ impl JobShop {
 
         max_by_jobs.max(max_by_machine)
     }
+
+    /// Builds a schedule by repeatedly starting, among the next operation of each job, the one that can start
+    /// the earliest. Returns the start time of each operation (indexed by `op_id`).
+    pub fn greedy_schedule(&self) -> Vec<i32> {
+        let mut starts = vec![0; self.num_jobs * self.num_machines];
+        let mut next_op = vec![0; self.num_jobs];
+        let mut job_end = vec![0; self.num_jobs];
+        let mut machine_end = vec![0; self.num_machines + 1];
+        for _ in 0..starts.len() {
+            let (start, job) = (0..self.num_jobs)
+                .filter(|&job| next_op[job] < self.num_machines)
+                .map(|job| (job_end[job].max(machine_end[self.machine(job, next_op[job])]), job))
+                .min()
+                .unwrap();
+            let op = next_op[job];
+            let end = start + self.duration(job, op);
+            starts[self.op_id(job, op)] = start;
+            job_end[job] = end;
+            machine_end[self.machine(job, op)] = end;
+            next_op[job] += 1;
+        }
+        starts
+    }
 }
 
 #[derive(Copy, Clone, Debug, Ord, PartialOrd, PartialEq, Eq, Hash)]
@@ -62,13 +85,26 @@ impl Into<usize> for TVar {
     }
 }
 
-use aries_model::lang::{BAtom, IVar};
+use aries_backtrack::{Backtrack, DecLvl};
+use aries_model::bounds::Bound;
+use aries_model::lang::{BAtom, IVar, IntCst, VarRef};
+use aries_solver::solver::brancher::{ActivityBrancher, Brancher, Decision};
+use aries_solver::solver::limits::SearchLimits;
+use aries_solver::solver::monitor::{ProgressReport, SearchMonitor};
+use aries_solver::solver::optimization::{
+    ObjectiveBounds, OptimizationResult, OptimizationStrategy, OPTIMIZATION_STRATEGY,
+};
+use aries_solver::solver::parallel::ParSolver;
+use aries_solver::solver::restarts::RestartPolicy;
+use aries_solver::solver::stats::{write_stats_file, Stats, StatsRecord};
 use aries_solver::solver::Solver;
 
+use aries_model::verification::verify_solution;
 use aries_model::Model;
 use aries_tnet::stn::IncSTN;
 use std::collections::HashMap;
 use std::fs;
+use std::time::Duration;
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -85,6 +121,39 @@ struct Opt {
     /// variables to their value in the best solution.
     #[structopt(long = "lns")]
     lns: Option<bool>,
+    /// Enables chronological backtracking on conflicts that would backjump over more than this number of
+    /// decision levels (0 disables it).
+    #[structopt(long = "chrono-backtrack")]
+    chrono_backtrack: Option<u32>,
+    /// Optimization strategy to use: linear, dichotomic or lb-linear
+    #[structopt(long = "strategy")]
+    strategy: Option<OptimizationStrategy>,
+    /// Maximum time (in seconds) allowed for the search. If reached, the best solution found is reported.
+    #[structopt(long = "timeout")]
+    timeout: Option<u64>,
+    /// Number of solvers to run in parallel, each with different search parameters.
+    #[structopt(long = "workers", default_value = "1")]
+    workers: usize,
+    /// Select decisions by ordering first the operations that can start the earliest,
+    /// instead of using the default activity-based heuristic.
+    #[structopt(long = "est-first")]
+    est_first: bool,
+    /// JSON file (as produced by `--save-solution`) whose values are used as the preferred values of the variables.
+    #[structopt(long = "warm-start")]
+    warm_start: Option<String>,
+    /// Guides the search towards the schedule built by a greedy dispatching rule,
+    /// by first deciding the start time of each operation to its value in this schedule.
+    #[structopt(long = "greedy-hint")]
+    greedy_hint: bool,
+    /// Periodically reports the progress of the search on the standard error.
+    #[structopt(long = "progress")]
+    progress: bool,
+    /// Writes the values of the variables in the best solution found to this JSON file.
+    #[structopt(long = "save-solution")]
+    save_solution: Option<String>,
+    /// Writes the statistics of the solver(s) to this file, in CSV if it has a `.csv` extension and in JSON otherwise.
+    #[structopt(long = "stats")]
+    stats: Option<String>,
 }
 
 fn main() {
@@ -99,34 +168,137 @@ fn main() {
     if let Some(use_lns) = opt.lns {
         aries_solver::solver::OPTIMIZE_USES_LNS.set(use_lns)
     }
+    if let Some(threshold) = opt.chrono_backtrack {
+        aries_solver::solver::CHRONO_BACKTRACK_THRESHOLD.set(threshold)
+    }
 
     let lower_bound = (opt.lower_bound).max(pb.makespan_lower_bound() as u32);
     println!("Initial lower bound: {}", lower_bound);
 
-    let (mut model, constraints, makespan) = encode(&pb, lower_bound, opt.upper_bound);
-    let stn = Box::new(IncSTN::new(model.new_write_token()));
-    let mut solver = Solver::new(model);
-    solver.add_theory(stn);
-    solver.enforce_all(&constraints);
-
-    let result = solver.minimize_with(makespan, |objective, _| {
-        println!("New solution with makespan: {}", objective)
-    });
-
-    if let Some((optimum, solution)) = result {
-        println!("Found optimal solution with makespan: {}", optimum);
-        assert_eq!(solution.lower_bound(makespan), optimum);
-        println!("{}", solver.stats);
-        if let Some(expected) = opt.expected_makespan {
-            assert_eq!(
-                optimum as u32, expected,
-                "The makespan found ({}) is not the expected one ({})",
-                optimum, expected
-            );
+    let (mut model, constraints, makespan, disjuncts, starts) = encode(&pb, lower_bound, opt.upper_bound);
+    let limits = match opt.timeout {
+        Some(timeout) => SearchLimits::none().with_timeout(Duration::from_secs(timeout)),
+        None => SearchLimits::none(),
+    };
+    let strategy = opt.strategy.unwrap_or(*OPTIMIZATION_STRATEGY.get());
+    let warm_start = match &opt.warm_start {
+        Some(file) => LabeledValues::read_from_file(file)
+            .and_then(|values| values.resolve(&model))
+            .expect("Cannot load warm start"),
+        None => Vec::new(),
+    };
+    let hint: Vec<(VarRef, IntCst)> = if opt.greedy_hint {
+        let schedule = pb.greedy_schedule();
+        starts.iter().map(|&start| VarRef::from(start)).zip(schedule).collect()
+    } else {
+        Vec::new()
+    };
+    // in debug builds, all solutions are checked against a copy of the original model
+    let original = if cfg!(debug_assertions) {
+        Some((model.clone(), constraints.clone()))
+    } else {
+        None
+    };
+    let on_progress = |_: ObjectiveBounds, solution: Option<&SavedAssignment>| {
+        if let (Some(solution), Some((model, constraints))) = (solution, &original) {
+            verify_solution(model, constraints, solution).expect("Invalid solution");
+        }
+    };
+
+    let (result, all_stats, records): (OptimizationResult, Vec<Stats>, Vec<StatsRecord>) = if opt.workers > 1 {
+        let mut solver = ParSolver::new(model, constraints, opt.workers);
+        solver.add_theory(|model| Box::new(IncSTN::new(model.new_write_token())));
+        if opt.est_first {
+            let disjuncts = &disjuncts;
+            solver.set_brancher(move |params| {
+                Box::new(EarliestStartFirst {
+                    disjuncts: disjuncts.clone(),
+                    fallback: ActivityBrancher::with_params(params.clone()),
+                })
+            });
+        }
+        solver.add_monitor(|worker| Box::new(MakespanReport { worker: Some(worker) }));
+        if opt.progress {
+            solver.add_monitor(|_| Box::new(ProgressReport::new(Duration::from_secs(1))));
         }
+        solver.set_limits(limits);
+        solver.hint(hint, true);
+        solver.warm_start(warm_start);
+        let result = solver.minimize_with_strategy(makespan, strategy, on_progress);
+        let records = solver.stats.iter().map(Stats::record).collect();
+        (result, solver.stats, records)
     } else {
-        eprintln!("NO SOLUTION");
-        assert!(opt.expected_makespan.is_none(), "Expected a valid solution");
+        let stn = Box::new(IncSTN::new(model.new_write_token()));
+        let brancher: Box<dyn Brancher> = if opt.est_first {
+            Box::new(EarliestStartFirst {
+                disjuncts,
+                fallback: ActivityBrancher::new(),
+            })
+        } else {
+            Box::new(ActivityBrancher::new())
+        };
+        let mut solver = Solver::with_brancher(model, brancher);
+        solver.add_theory(stn);
+        solver.enforce_all(&constraints);
+        solver.add_monitor(Box::new(MakespanReport { worker: None }));
+        if opt.progress {
+            solver.add_monitor(Box::new(ProgressReport::new(Duration::from_secs(1))));
+        }
+        solver.set_limits(limits);
+        solver.hint(&hint, true);
+        if !warm_start.is_empty() {
+            solver.warm_start(&warm_start);
+        }
+        let result = solver.minimize_with_strategy(makespan, strategy, on_progress);
+        let records = vec![solver.stats_record()];
+        (result, vec![solver.stats], records)
+    };
+    let print_stats = || {
+        for (worker, stats) in all_stats.iter().enumerate() {
+            if all_stats.len() > 1 {
+                println!("====== Worker {}", worker);
+            }
+            println!("{}", stats);
+        }
+    };
+
+    if let Some(file) = &opt.stats {
+        write_stats_file(&records, file.as_ref()).expect("Cannot write statistics");
+    }
+
+    if let (Some(file), Some((_, solution))) = (&opt.save_solution, result.best()) {
+        LabeledValues::from_solution(solution, solution)
+            .write_to_file(file)
+            .expect("Cannot save solution");
+    }
+
+    match result {
+        OptimizationResult::Optimal { value, solution } => {
+            println!("Found optimal solution with makespan: {}", value);
+            assert_eq!(solution.lower_bound(makespan), value);
+            print_stats();
+            if let Some(expected) = opt.expected_makespan {
+                assert_eq!(
+                    value as u32, expected,
+                    "The makespan found ({}) is not the expected one ({})",
+                    value, expected
+                );
+            }
+        }
+        OptimizationResult::Feasible { value, lower_bound, .. } => {
+            println!(
+                "Found solution with makespan {} (optimal makespan in [{}, {}])",
+                value, lower_bound, value
+            );
+            print_stats();
+        }
+        OptimizationResult::Infeasible => {
+            eprintln!("NO SOLUTION");
+            assert!(opt.expected_makespan.is_none(), "Expected a valid solution");
+        }
+        OptimizationResult::Unknown { lower_bound } => {
+            eprintln!("NO SOLUTION FOUND (makespan >= {})", lower_bound);
+        }
     }
     println!("TOTAL RUNTIME: {:.6}", start_time.elapsed().as_secs_f64());
 }
@@ -161,12 +333,15 @@ fn parse(input: &str) -> JobShop {
     }
 }
 
-fn encode(pb: &JobShop, lower_bound: u32, upper_bound: u32) -> (Model, Vec<BAtom>, IVar) {
+/// Encodes the problem, returning the model, its constraints, the makespan variable, the pairs of operations
+/// that cannot overlap and the start variable of each operation (indexed by `op_id`).
+fn encode(pb: &JobShop, lower_bound: u32, upper_bound: u32) -> (Model, Vec<BAtom>, IVar, Vec<Disjunct>, Vec<IVar>) {
     let lower_bound = lower_bound as i32;
     let upper_bound = upper_bound as i32;
     let mut m = Model::new();
     let mut hmap: HashMap<TVar, IVar> = HashMap::new();
     let mut constraints = Vec::new();
+    let mut disjuncts = Vec::new();
 
     let makespan_variable = m.new_ivar(lower_bound, upper_bound, "makespan");
     for j in 0..pb.num_jobs {
@@ -195,9 +370,139 @@ fn encode(pb: &JobShop, lower_bound: u32, upper_bound: u32) -> (Model, Vec<BAtom
                 let o1 = m.leq(tji1 + pb.duration(j1, i1), tji2);
                 let o2 = m.leq(tji2 + pb.duration(j2, i2), tji1);
                 constraints.push(m.or2(o1, o2));
+                disjuncts.push(Disjunct {
+                    first: tji1,
+                    second: tji2,
+                    first_before_second: o1,
+                    second_before_first: o2,
+                });
             }
         }
     }
 
-    (m, constraints, makespan_variable)
+    let starts = (0..pb.num_jobs)
+        .flat_map(|j| (0..pb.num_machines).map(move |i| (j, i)))
+        .map(|(j, i)| hmap[&pb.tvar(j, i)])
+        .collect();
+    (m, constraints, makespan_variable, disjuncts, starts)
+}
+
+/// Monitor that prints each improvement of the bounds of the makespan found by a solver
+/// (identified by its index among the workers of a parallel solver, if any).
+struct MakespanReport {
+    worker: Option<usize>,
+}
+
+impl SearchMonitor for MakespanReport {
+    fn on_objective_bounds(&mut self, bounds: ObjectiveBounds, solution: Option<&SavedAssignment>, _stats: &Stats) {
+        // a single line is printed at once, so that the reports of concurrent workers are not interleaved
+        let prefix = match self.worker {
+            Some(worker) => format!("[worker {}] ", worker),
+            None => String::new(),
+        };
+        if solution.is_some() {
+            println!("{}New solution with makespan: {}", prefix, bounds.ub);
+        } else {
+            println!("{}New lower bound: {}", prefix, bounds.lb)
+        }
+    }
+}
+
+/// Two operations that require the same machine, together with the literals stating that the first (resp. second)
+/// operation ends before the start of the other.
+#[derive(Clone)]
+struct Disjunct {
+    first: IVar,
+    second: IVar,
+    first_before_second: BAtom,
+    second_before_first: BAtom,
+}
+
+/// Brancher that orders the operations on each machine: it selects the unordered pair of operations with the
+/// earliest possible start and places the operation that can start the earliest first.
+/// Once all operations are ordered, the remaining decisions are delegated to the default activity-based brancher,
+/// which also determines when to restart.
+struct EarliestStartFirst {
+    disjuncts: Vec<Disjunct>,
+    fallback: ActivityBrancher,
+}
+
+impl Brancher for EarliestStartFirst {
+    fn next_decision(&mut self, stats: &Stats, model: &Model) -> Option<Decision> {
+        let fallback_decision = self.fallback.next_decision(stats, model);
+        if let Some(Decision::Restart) = fallback_decision {
+            return fallback_decision;
+        }
+        let literal = |atom: BAtom| match atom {
+            BAtom::Bound(l) => Some(l),
+            BAtom::Expr(e) => model.literal_of_expr(e),
+            BAtom::Cst(_) => None,
+        };
+        let mut best: Option<(IntCst, Bound)> = None;
+        for d in &self.disjuncts {
+            let (first, second) = match (literal(d.first_before_second), literal(d.second_before_first)) {
+                (Some(first), Some(second)) => (first, second),
+                _ => continue,
+            };
+            let (first_value, second_value) = (model.value_of_literal(first), model.value_of_literal(second));
+            if first_value == Some(true) || second_value == Some(true) {
+                // already ordered
+                continue;
+            }
+            let est_first = model.domain_of(d.first).0;
+            let est_second = model.domain_of(d.second).0;
+            let est = est_first.min(est_second);
+            if matches!(best, Some((best_est, _)) if best_est <= est) {
+                continue;
+            }
+            let decision = if second_value == Some(false) || (first_value.is_none() && est_first <= est_second) {
+                first
+            } else {
+                second
+            };
+            best = Some((est, decision));
+        }
+        match best {
+            Some((_, literal)) => Some(Decision::SetLiteral(literal)),
+            None => fallback_decision,
+        }
+    }
+
+    fn bump_activity(&mut self, var: VarRef) {
+        self.fallback.bump_activity(var)
+    }
+
+    fn clause_learnt(&mut self, clause: &[Bound], lbd: u32) {
+        self.fallback.clause_learnt(clause, lbd)
+    }
+
+    fn restart_policy(&self) -> Option<RestartPolicy> {
+        self.fallback.restart_policy()
+    }
+
+    fn solution_found(&mut self, assignment: &Model) {
+        self.fallback.solution_found(assignment)
+    }
+
+    fn set_default_value(&mut self, var: VarRef, value: IntCst) {
+        self.fallback.set_default_value(var, value)
+    }
+
+    fn prioritize(&mut self, var: VarRef) {
+        self.fallback.prioritize(var)
+    }
+}
+
+impl Backtrack for EarliestStartFirst {
+    fn save_state(&mut self) -> DecLvl {
+        self.fallback.save_state()
+    }
+
+    fn num_saved(&self) -> u32 {
+        self.fallback.num_saved()
+    }
+
+    fn restore_last(&mut self) {
+        self.fallback.restore_last()
+    }
 }
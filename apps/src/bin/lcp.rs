@@ -8,14 +8,25 @@ use aries_planning::chronicles::*;
 use aries_collections::ref_store::{Ref, RefVec};
 use aries_planning::chronicles::constraints::ConstraintType;
 
+use aries_backtrack::{Backtrack, DecLvl};
 use aries_model::assignments::{Assignment, SavedAssignment};
-use aries_model::lang::{Atom, BAtom, BVar, IAtom, IVar, SAtom, Variable};
+use aries_model::bounds::Bound;
+use aries_model::lang::{Atom, BAtom, BVar, IAtom, IVar, IntCst, SAtom, VarRef, Variable};
 use aries_model::symbols::SymId;
+use aries_model::verification::verify_solution;
 use aries_model::Model;
 use aries_planning::chronicles::Task;
 use aries_planning::classical::from_chronicles;
 use aries_planning::parsing::pddl::{parse_pddl_domain, parse_pddl_problem, PddlFeature};
 use aries_planning::parsing::pddl_to_chronicles;
+use aries_solver::solver::brancher::{ActivityBrancher, Brancher, Decision};
+use aries_solver::solver::limits::SearchLimits;
+use aries_solver::solver::monitor::{ProgressReport, SearchMonitor};
+use aries_solver::solver::optimization::{ObjectiveBounds, OPTIMIZATION_STRATEGY};
+use aries_solver::solver::restarts::RestartPolicy;
+use aries_solver::solver::stats::Stats;
+use aries_solver::solver::SolveResult;
+use aries_solver::theories::csp::CSP;
 use aries_solver::*;
 use aries_tnet::stn::{Edge, IncSTN, Timepoint};
 use aries_tnet::*;
@@ -28,7 +39,7 @@ use std::fmt::Write as FmtWrite;
 use std::fs::File;
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use structopt::StructOpt;
 
 /// Generates chronicles from a PDDL problem specification.
@@ -46,6 +57,12 @@ struct Opt {
     max_actions: Option<u32>,
     #[structopt(long = "optimize")]
     optimize_makespan: bool,
+    /// Maximum time (in seconds) allowed for the whole planning process.
+    #[structopt(long)]
+    timeout: Option<u64>,
+    /// Periodically reports the progress of the search on the standard error.
+    #[structopt(long)]
+    progress: bool,
 }
 
 /// Parameter that defines the symmetry breaking strategy to use.
@@ -53,6 +70,10 @@ struct Opt {
 /// Possible values are `none` and `simple` (default).
 static SYMMETRY_BREAKING: EnvParam<SymmetryBreakingType> = EnvParam::new("ARIES_LCP_SYMMETRY_BREAKING", "simple");
 
+/// If true, the solver first decides which chronicles are present in the plan, before making any other decision.
+/// The value of this parameter is loaded from the environment variable `ARIES_LCP_PRESENCE_FIRST`.
+static PRESENCE_FIRST: EnvParam<bool> = EnvParam::new("ARIES_LCP_PRESENCE_FIRST", "false");
+
 /// The type of symmetry breaking to apply to problems.
 #[derive(Copy, Clone)]
 enum SymmetryBreakingType {
@@ -108,7 +129,16 @@ fn main() -> Result<()> {
     aries_planning::chronicles::preprocessing::preprocess(&mut spec);
     println!("==========================");
 
+    let limits = match opt.timeout {
+        Some(timeout) => SearchLimits::none().with_timeout(Duration::from_secs(timeout)),
+        None => SearchLimits::none(),
+    };
+
     for n in opt.min_actions..opt.max_actions.unwrap_or(u32::max_value()) {
+        if limits.deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+            println!("Timeout");
+            break;
+        }
         println!("{} Solving with {} actions", n, n);
         let start = Instant::now();
         let mut pb = FiniteProblem {
@@ -125,7 +155,7 @@ fn main() -> Result<()> {
         }
         println!("  [{:.3}s] Populated", start.elapsed().as_secs_f32());
         let start = Instant::now();
-        let result = solve(&pb, opt.optimize_makespan);
+        let result = solve(&pb, opt.optimize_makespan, &limits, opt.progress);
         println!("  [{:.3}s] solved", start.elapsed().as_secs_f32());
         match result {
             Some(x) => {
@@ -254,31 +284,66 @@ fn refinements_of_task<'a>(task: &Task, pb: &FiniteProblem, spec: &'a Problem) -
     candidates
 }
 
-fn solve(pb: &FiniteProblem, optimize_makespan: bool) -> Option<SavedAssignment> {
+fn solve(
+    pb: &FiniteProblem,
+    optimize_makespan: bool,
+    limits: &SearchLimits,
+    progress: bool,
+) -> Option<SavedAssignment> {
     let (mut model, constraints) = encode(&pb).unwrap(); // TODO: report error
+
+    // in debug builds, all solutions are checked against a copy of the original model
+    let original = if cfg!(debug_assertions) {
+        Some((model.clone(), constraints.clone()))
+    } else {
+        None
+    };
+    let check = |solution: &SavedAssignment| {
+        if let Some((model, constraints)) = &original {
+            verify_solution(model, constraints, solution).expect("Invalid solution");
+        }
+    };
     let stn = Box::new(IncSTN::new(model.new_write_token()));
-    let mut solver = aries_solver::solver::Solver::new(model);
+    let csp = Box::new(CSP::new(model.new_write_token()));
+    let brancher: Box<dyn Brancher> = if *PRESENCE_FIRST.get() {
+        Box::new(PresenceFirst {
+            presences: pb.chronicles.iter().map(|ch| ch.chronicle.presence).collect(),
+            fallback: ActivityBrancher::new(),
+        })
+    } else {
+        Box::new(ActivityBrancher::new())
+    };
+    let mut solver = aries_solver::solver::Solver::with_brancher(model, brancher);
     solver.add_theory(stn);
+    solver.add_theory(csp);
     solver.enforce_all(&constraints);
+    solver.set_limits(limits.clone());
+    if progress {
+        solver.add_monitor(Box::new(ProgressReport::new(Duration::from_secs(1))));
+    }
 
     let found_plan = if optimize_makespan {
-        let res = solver.minimize_with(pb.horizon, |makespan, ass| {
-            println!(
-                "\nFound plan with makespan: {}\n{}",
-                makespan,
-                format_pddl_plan(&pb, ass).unwrap_or_else(|e| format!("Error while formatting:\n{}", e))
-            );
+        let strategy = *OPTIMIZATION_STRATEGY.get();
+        solver.add_monitor(Box::new(PlanReport { pb: pb.clone() }));
+        let res = solver.minimize_with_strategy(pb.horizon, strategy, |_, ass| {
+            if let Some(ass) = ass {
+                check(ass)
+            }
         });
-        res.map(|tup| tup.1)
+        if res.is_optimal() {
+            println!("Plan proved optimal");
+        }
+        res.into_best().map(|tup| tup.1)
     } else {
-        if solver.solve() {
-            Some(solver.model.clone())
+        if solver.solve() == SolveResult::Sat {
+            Some(solver.solution())
         } else {
             None
         }
     };
 
     if let Some(solution) = found_plan {
+        check(&solution);
         println!("{}", &solver.stats);
         Some(solution)
     } else {
@@ -286,6 +351,94 @@ fn solve(pb: &FiniteProblem, optimize_makespan: bool) -> Option<SavedAssignment>
     }
 }
 
+/// Monitor that prints each plan found while optimizing the makespan, as well as the improvements of its lower bound.
+struct PlanReport {
+    pb: FiniteProblem,
+}
+
+impl SearchMonitor for PlanReport {
+    fn on_objective_bounds(&mut self, bounds: ObjectiveBounds, solution: Option<&SavedAssignment>, _stats: &Stats) {
+        match solution {
+            Some(ass) => println!(
+                "\nFound plan with makespan: {}\n{}",
+                bounds.ub,
+                format_pddl_plan(&self.pb, ass).unwrap_or_else(|e| format!("Error while formatting:\n{}", e))
+            ),
+            None => println!("Makespan lower bound: {}", bounds.lb),
+        }
+    }
+}
+
+/// Brancher that decides the presence of all chronicles before delegating to the default activity-based brancher.
+/// Chronicles are considered in order and are preferably absent (resp. present) if the fallback brancher
+/// prefers minimal (resp. maximal) values.
+struct PresenceFirst {
+    presences: Vec<BAtom>,
+    fallback: ActivityBrancher,
+}
+
+impl Brancher for PresenceFirst {
+    fn next_decision(&mut self, stats: &Stats, model: &Model) -> Option<Decision> {
+        // the fallback is in charge of the restart policy
+        let fallback_decision = self.fallback.next_decision(stats, model);
+        if let Some(Decision::Restart) = fallback_decision {
+            return fallback_decision;
+        }
+        let undecided = self
+            .presences
+            .iter()
+            .filter_map(|&presence| match presence {
+                BAtom::Bound(l) => Some(l),
+                BAtom::Expr(e) => model.literal_of_expr(e),
+                BAtom::Cst(_) => None,
+            })
+            .find(|&l| model.value_of_literal(l).is_none());
+        match undecided {
+            Some(presence) if self.fallback.params.prefer_min_value => Some(Decision::SetLiteral(!presence)),
+            Some(presence) => Some(Decision::SetLiteral(presence)),
+            None => fallback_decision,
+        }
+    }
+
+    fn bump_activity(&mut self, var: VarRef) {
+        self.fallback.bump_activity(var)
+    }
+
+    fn clause_learnt(&mut self, clause: &[Bound], lbd: u32) {
+        self.fallback.clause_learnt(clause, lbd)
+    }
+
+    fn restart_policy(&self) -> Option<RestartPolicy> {
+        self.fallback.restart_policy()
+    }
+
+    fn solution_found(&mut self, assignment: &Model) {
+        self.fallback.solution_found(assignment)
+    }
+
+    fn set_default_value(&mut self, var: VarRef, value: IntCst) {
+        self.fallback.set_default_value(var, value)
+    }
+
+    fn prioritize(&mut self, var: VarRef) {
+        self.fallback.prioritize(var)
+    }
+}
+
+impl Backtrack for PresenceFirst {
+    fn save_state(&mut self) -> DecLvl {
+        self.fallback.save_state()
+    }
+
+    fn num_saved(&self) -> u32 {
+        self.fallback.num_saved()
+    }
+
+    fn restore_last(&mut self) {
+        self.fallback.restore_last()
+    }
+}
+
 #[derive(Eq, PartialEq, Hash, Copy, Clone)]
 enum Var {
     Boolean(BAtom, IAtom),
@@ -533,18 +686,26 @@ fn encode(pb: &FiniteProblem) -> anyhow::Result<(Model, Vec<BAtom>)> {
         for constraint in &instance.chronicle.constraints {
             match constraint.tpe {
                 ConstraintType::InTable { table_id } => {
-                    let mut supported_by_a_line = Vec::with_capacity(256);
-                    supported_by_a_line.push(!instance.chronicle.presence);
+                    let table = &pb.tables[table_id as usize];
                     let vars = &constraint.variables;
-                    for values in pb.tables[table_id as usize].lines() {
-                        assert_eq!(vars.len(), values.len());
-                        let mut supported_by_this_line = Vec::with_capacity(16);
-                        for (&var, &val) in vars.iter().zip(values.iter()) {
-                            supported_by_this_line.push(model.eq(var, val));
+                    let int_vars: Option<Vec<IAtom>> = vars.iter().map(|&v| v.int_view()).collect();
+                    let in_table = if let Some(int_vars) = int_vars {
+                        // handled by a dedicated propagator
+                        model.in_table(&int_vars, table.lines())
+                    } else {
+                        // some variables have no integer view (e.g. boolean expressions), decompose into clauses
+                        let mut supported_by_a_line = Vec::with_capacity(256);
+                        for values in table.lines() {
+                            assert_eq!(vars.len(), values.len());
+                            let mut supported_by_this_line = Vec::with_capacity(16);
+                            for (&var, &val) in vars.iter().zip(values.iter()) {
+                                supported_by_this_line.push(model.eq(var, val));
+                            }
+                            supported_by_a_line.push(model.and(&supported_by_this_line));
                         }
-                        supported_by_a_line.push(model.and(&supported_by_this_line));
-                    }
-                    constraints.push(model.or(&supported_by_a_line));
+                        model.or(&supported_by_a_line)
+                    };
+                    constraints.push(model.implies(instance.chronicle.presence, in_table));
                 }
                 ConstraintType::LT => match constraint.variables.as_slice() {
                     &[a, b] => {
@@ -1,8 +1,39 @@
-use aries_model::assignments::Assignment;
-use aries_model::lang::{BAtom, IVar};
-use aries_model::Model;
-use aries_solver::solver::Solver;
+use aries_backtrack::{Backtrack, DecLvl};
+use aries_model::assignments::{Assignment, LabeledValues, SavedAssignment};
+use aries_model::bounds::Bound;
+use aries_model::lang::{BAtom, IAtom, IVar, IntCst, LinearSum, VarRef};
+use aries_model::verification::verify_solution;
+use aries_model::{Model, WriterId};
+use aries_solver::solver::brancher::{Brancher, Decision};
+use aries_solver::solver::limits::SearchLimits;
+use aries_solver::solver::monitor::SearchMonitor;
+use aries_solver::solver::optimization::{ObjectiveBounds, OptimizationResult, OptimizationStrategy};
+use aries_solver::solver::parallel::ParSolver;
+use aries_solver::solver::preprocessing::PreprocessingParams;
+use aries_solver::solver::stats::{StatValue, Stats};
+use aries_solver::solver::{SolveResult, Solver};
+use aries_solver::theories::csp::CSP;
+use aries_solver::theories::linear::LinearTheory;
+use aries_solver::Theory;
 use aries_tnet::stn::IncSTN;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::sync::atomic::Ordering;
+
+/// Builds a solver for a copy of the `model`, with the theory created by `theory`, and enforces the `constraints`.
+fn build_solver<T: Theory + 'static>(
+    model: &Model,
+    theory: impl FnOnce(WriterId) -> T,
+    constraints: &[BAtom],
+) -> Solver {
+    let mut model = model.clone();
+    let theory = theory(model.new_write_token());
+    let mut solver = Solver::new(model);
+    solver.add_theory(Box::new(theory));
+    solver.enforce_all(constraints);
+    solver
+}
 
 #[test]
 fn sat() {
@@ -12,17 +43,292 @@ fn sat() {
 
     let mut solver = Solver::new(model);
     solver.enforce(a);
-    assert!(solver.solve());
+    assert_eq!(solver.solve(), SolveResult::Sat);
     assert_eq!(solver.model.boolean_value_of(a), Some(true));
     let c = solver.model.implies(a, b);
     solver.enforce(c);
-    assert!(solver.solve());
+    assert_eq!(solver.solve(), SolveResult::Sat);
     assert_eq!(solver.model.boolean_value_of(a), Some(true));
     assert_eq!(solver.model.boolean_value_of(b), Some(true));
 
     solver.enforce(!b);
 
-    assert!(!solver.solve());
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+}
+
+#[test]
+fn assumptions() {
+    let mut model = Model::new();
+    let a = model.new_bvar("a");
+    let b = model.new_bvar("b");
+    let c = model.new_bvar("c");
+    let a_implies_b = model.implies(a, b);
+
+    let mut solver = Solver::new(model);
+    solver.enforce(a_implies_b);
+
+    assert_eq!(
+        solver.solve_with_assumptions(&[a.true_lit(), b.false_lit()]),
+        SolveResult::Unsat
+    );
+    assert_eq!(solver.failed_assumption(), Some(b.false_lit()));
+
+    assert_eq!(
+        solver.solve_with_assumptions(&[a.true_lit(), c.false_lit()]),
+        SolveResult::Sat
+    );
+    assert_eq!(solver.failed_assumption(), None);
+    assert_eq!(solver.model.boolean_value_of(b), Some(true));
+    assert_eq!(solver.model.boolean_value_of(c), Some(false));
+
+    // assumptions of the previous calls have been retracted
+    assert_eq!(
+        solver.solve_with_assumptions(&[b.false_lit(), c.true_lit()]),
+        SolveResult::Sat
+    );
+    assert_eq!(solver.model.boolean_value_of(a), Some(false));
+    assert_eq!(solver.solve(), SolveResult::Sat);
+}
+
+#[test]
+fn int_assumptions() {
+    let mut model = Model::new();
+    let a = model.new_ivar(0, 10, "a");
+    let b = model.new_ivar(0, 10, "b");
+    let constraint = model.lt(a, b);
+
+    let theory = IncSTN::new(model.new_write_token());
+    let mut solver = Solver::new(model);
+    solver.add_theory(Box::new(theory));
+    solver.enforce(constraint);
+
+    let a_geq_5 = Bound::geq(a, 5);
+    let b_leq_5 = Bound::leq(b, 5);
+    let b_leq_8 = Bound::leq(b, 8);
+    assert_eq!(solver.solve_with_assumptions(&[a_geq_5, b_leq_5]), SolveResult::Unsat);
+    assert_eq!(solver.failed_assumption(), Some(b_leq_5));
+    assert_eq!(solver.solve_with_assumptions(&[a_geq_5, b_leq_8]), SolveResult::Sat);
+    assert!(solver.model.lower_bound(a) >= 5);
+    assert!(solver.model.upper_bound(b) <= 8);
+}
+
+#[test]
+fn unsat_core() {
+    let mut model = Model::new();
+    let a = model.new_bvar("a");
+    let b = model.new_bvar("b");
+    let c = model.new_bvar("c");
+    let d = model.new_bvar("d");
+    let x = model.new_bvar("x");
+    let constraints = vec![model.implies(a, x), model.implies(b, !x)];
+
+    let mut solver = Solver::new(model);
+    solver.enforce_all(&constraints);
+
+    let assumptions = [c.true_lit(), a.true_lit(), d.true_lit(), b.true_lit()];
+    assert_eq!(solver.solve_with_assumptions(&assumptions), SolveResult::Unsat);
+    assert_eq!(solver.unsat_core(), &[a.true_lit(), b.true_lit()]);
+    assert_eq!(solver.minimize_unsat_core(), vec![a.true_lit(), b.true_lit()]);
+
+    assert_eq!(
+        solver.solve_with_assumptions(&[c.true_lit(), a.true_lit()]),
+        SolveResult::Sat
+    );
+    assert!(solver.unsat_core().is_empty());
+}
+
+#[test]
+fn minimal_unsat_core() {
+    let mut model = Model::new();
+    let a = model.new_ivar(0, 10, "a");
+    let b = model.new_ivar(0, 10, "b");
+    let c = model.new_ivar(0, 10, "c");
+    let constraints = vec![model.lt(a, b), model.lt(b, c)];
+
+    let theory = IncSTN::new(model.new_write_token());
+    let mut solver = Solver::new(model);
+    solver.add_theory(Box::new(theory));
+    solver.enforce_all(&constraints);
+
+    let assumptions = [
+        Bound::geq(a, 2),
+        Bound::leq(b, 8),
+        Bound::geq(a, 5),
+        Bound::leq(c, 9),
+        Bound::leq(c, 6),
+    ];
+    assert_eq!(solver.solve_with_assumptions(&assumptions), SolveResult::Unsat);
+    let core = solver.unsat_core().to_vec();
+    assert!(core.contains(&Bound::leq(c, 6)));
+    let minimal = solver.minimize_unsat_core();
+    assert_eq!(minimal, vec![Bound::geq(a, 5), Bound::leq(c, 6)]);
+    assert!(minimal.iter().all(|l| core.contains(l)));
+    assert_eq!(solver.solve_with_assumptions(&minimal), SolveResult::Unsat);
+}
+
+#[test]
+fn tracked_constraints() {
+    let mut model = Model::new();
+    let a = model.new_ivar(0, 10, "a");
+    let b = model.new_ivar(0, 10, "b");
+    let c = model.new_ivar(0, 10, "c");
+    let a_leq_5 = model.leq(a, 5);
+    let a_lt_b = model.lt(a, b);
+    let b_lt_c = model.lt(b, c);
+    let c_lt_a = model.lt(c, a);
+    let b_leq_c = model.leq(b, c);
+
+    let theory = IncSTN::new(model.new_write_token());
+    let mut solver = Solver::new(model);
+    solver.add_theory(Box::new(theory));
+
+    solver.enforce_tracked("a <= 5", a_leq_5);
+    solver.enforce_tracked("a < b", a_lt_b);
+    solver.enforce_tracked("b <= c", b_leq_c);
+    solver.enforce_tracked("b < c", b_lt_c);
+    assert_eq!(solver.solve(), SolveResult::Sat);
+    assert!(solver.model.lower_bound(c) >= 2);
+
+    solver.enforce_tracked("c < a", c_lt_a);
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+    let conflict: Vec<_> = solver
+        .conflicting_constraints()
+        .iter()
+        .map(|label| label.get().unwrap().to_string())
+        .collect();
+    assert_eq!(conflict.len(), 3);
+    assert!(conflict.contains(&"a < b".to_string()));
+    assert!(conflict.contains(&"c < a".to_string()));
+    assert!(conflict.contains(&"b < c".to_string()) || conflict.contains(&"b <= c".to_string()));
+}
+
+#[test]
+fn enumerate() {
+    let mut model = Model::new();
+    let a = model.new_bvar("a");
+    let b = model.new_bvar("b");
+    let c = model.new_bvar("c");
+    let at_least_one = model.or(&[a.into(), b.into(), c.into()]);
+
+    let mut solver = Solver::new(model);
+    solver.enforce(at_least_one);
+
+    let solutions: Vec<_> = solver.enumerate(&[a.into(), b.into(), c.into()]).collect();
+    assert_eq!(solutions.len(), 7);
+    let mut distinct = HashSet::new();
+    for sol in &solutions {
+        let values = (
+            sol.boolean_value_of(a).unwrap(),
+            sol.boolean_value_of(b).unwrap(),
+            sol.boolean_value_of(c).unwrap(),
+        );
+        assert!(values.0 || values.1 || values.2);
+        distinct.insert(values);
+    }
+    assert_eq!(distinct.len(), 7);
+
+    // all other solutions have been blocked, while the last one is forced at the root level and could not be
+    let last = solutions.last().unwrap();
+    assert_eq!(solver.solve(), SolveResult::Sat);
+    for v in [a, b, c].iter().copied() {
+        assert_eq!(solver.model.boolean_value_of(v), last.boolean_value_of(v));
+    }
+}
+
+#[test]
+fn enumerate_no_variables() {
+    let mut model = Model::new();
+    let a = model.new_bvar("a");
+    let b = model.new_bvar("b");
+    let at_least_one = model.or(&[a.into(), b.into()]);
+
+    let mut solver = Solver::new(model);
+    solver.enforce(at_least_one);
+
+    // all solutions are identical when projected on no variables
+    assert_eq!(solver.enumerate(&[]).count(), 1);
+    // no clause could block the solution, the problem remains satisfiable
+    assert_eq!(solver.solve(), SolveResult::Sat);
+}
+
+#[test]
+fn enumerate_projected() {
+    let mut model = Model::new();
+    let a = model.new_ivar(0, 3, "a");
+    let b = model.new_ivar(0, 3, "b");
+    let c = model.new_ivar(0, 3, "c");
+    let constraints = vec![model.lt(a, b), model.lt(b, c)];
+
+    let theory = IncSTN::new(model.new_write_token());
+    let mut solver = Solver::new(model);
+    solver.add_theory(Box::new(theory));
+    solver.enforce_all(&constraints);
+
+    let values: HashSet<_> = solver
+        .enumerate(&[a.into(), b.into()])
+        .map(|sol| (sol.domain_of(a), sol.domain_of(b)))
+        .collect();
+    let expected: HashSet<_> = vec![((0, 0), (1, 1)), ((0, 0), (2, 2)), ((1, 1), (2, 2))]
+        .into_iter()
+        .collect();
+    assert_eq!(values, expected);
+}
+
+#[test]
+fn enumerate_preprocessed() {
+    let mut model = Model::new();
+    let a = model.new_bvar("a");
+    let b = model.new_bvar("b");
+    let c = model.new_bvar("c");
+    let x = model.new_bvar("x");
+    // x only links a and b: a | x, x => b
+    let constraints = vec![
+        model.or(&[a.into(), x.into()]),
+        model.implies(x, b),
+        model.or(&[b.into(), c.into()]),
+        model.or(&[(!a).into(), (!c).into()]),
+    ];
+
+    let mut solver = Solver::new(model);
+    solver.enforce_all(&constraints);
+    solver.preprocess(&PreprocessingParams::default(), &[a.into(), b.into(), c.into()]);
+    assert!(solver.stats.preprocessing.eliminated >= 1);
+
+    let mut values = HashSet::new();
+    for sol in solver.enumerate(&[a.into(), b.into(), c.into()]) {
+        let (a, b, c, x) = (
+            sol.boolean_value_of(a).unwrap(),
+            sol.boolean_value_of(b).unwrap(),
+            sol.boolean_value_of(c).unwrap(),
+            sol.boolean_value_of(x).unwrap(),
+        );
+        // the eliminated variable is given a value satisfying the original clauses
+        assert!(a || x);
+        assert!(!x || b);
+        assert!(values.insert((a, b, c)));
+    }
+    // projection of the original clauses on a, b and c: (a | b) & (b | c) & (!a | !c)
+    let expected: HashSet<_> = vec![(false, true, false), (false, true, true), (true, true, false)]
+        .into_iter()
+        .collect();
+    assert_eq!(values, expected);
+}
+
+#[test]
+#[should_panic(expected = "must be frozen")]
+fn enumerate_eliminated() {
+    let mut model = Model::new();
+    let a = model.new_bvar("a");
+    let b = model.new_bvar("b");
+    let x = model.new_bvar("x");
+    let constraints = vec![model.or(&[a.into(), x.into()]), model.implies(x, b)];
+
+    let mut solver = Solver::new(model);
+    solver.enforce_all(&constraints);
+    solver.preprocess(&PreprocessingParams::default(), &[a.into(), b.into()]);
+    assert!(solver.stats.preprocessing.eliminated >= 1);
+    // x was not frozen and has been eliminated
+    let _ = solver.enumerate(&[x.into()]);
 }
 
 #[test]
@@ -39,7 +345,7 @@ fn diff_logic() {
 
     solver.add_theory(Box::new(theory));
     solver.enforce_all(&constraints);
-    assert!(!solver.solve());
+    assert_eq!(solver.solve(), SolveResult::Unsat);
 }
 
 #[test]
@@ -58,7 +364,7 @@ fn minimize() {
 
     solver.add_theory(Box::new(theory));
     solver.enforce_all(&constraints);
-    assert!(solver.solve());
+    assert_eq!(solver.solve(), SolveResult::Sat);
     match solver.minimize(c) {
         None => panic!(),
         Some((val, _)) => assert_eq!(val, 7),
@@ -66,6 +372,96 @@ fn minimize() {
     solver.print_stats()
 }
 
+/// Minimizes the makespan of a small job shop and returns it, together with the number of chronological backtracks.
+fn solve_jobshop(chrono_backtrack_threshold: u32) -> (IntCst, u64) {
+    // (machine, duration) of each operation, in the order of their job
+    let jobs = [
+        [(0, 5), (1, 4), (2, 3), (3, 6)],
+        [(1, 3), (0, 6), (3, 2), (2, 4)],
+        [(2, 4), (3, 3), (0, 5), (1, 2)],
+        [(3, 2), (2, 5), (1, 4), (0, 3)],
+    ];
+    let mut model = Model::new();
+    let horizon = jobs.iter().flatten().map(|op| op.1).sum();
+    let makespan = model.new_ivar(0, horizon, "makespan");
+    let mut constraints = Vec::new();
+    let mut ops = Vec::new();
+    for (j, job) in jobs.iter().enumerate() {
+        let mut previous: Option<(IVar, IntCst)> = None;
+        for (i, &(machine, duration)) in job.iter().enumerate() {
+            let start = model.new_ivar(0, horizon, format!("start({}, {})", j, i));
+            if let Some((prev_start, prev_duration)) = previous {
+                constraints.push(model.leq(prev_start + prev_duration, start));
+            }
+            constraints.push(model.leq(start + duration, makespan));
+            ops.push((machine, start, duration));
+            previous = Some((start, duration));
+        }
+    }
+    for (k, &(m1, s1, d1)) in ops.iter().enumerate() {
+        for &(m2, s2, d2) in &ops[k + 1..] {
+            if m1 == m2 {
+                let first = model.leq(s1 + d1, s2);
+                let second = model.leq(s2 + d2, s1);
+                constraints.push(model.or2(first, second));
+            }
+        }
+    }
+
+    let stn = IncSTN::new(model.new_write_token());
+    let mut solver = Solver::new(model);
+    solver.add_theory(Box::new(stn));
+    solver.enforce_all(&constraints);
+    solver.set_chrono_backtrack_threshold(chrono_backtrack_threshold);
+    let (value, _) = solver.minimize(makespan).expect("No solution");
+    (value, solver.stats.num_chrono_backtracks)
+}
+
+#[test]
+fn chronological_backtracking() {
+    let (optimum, chrono_backtracks) = solve_jobshop(0);
+    assert_eq!(chrono_backtracks, 0);
+
+    // backtrack chronologically instead of any non-chronological backjump
+    let (value, chrono_backtracks) = solve_jobshop(1);
+    assert_eq!(value, optimum);
+    assert!(chrono_backtracks > 0);
+}
+
+#[test]
+fn stats_record() {
+    let mut model = Model::new();
+    let a = model.new_ivar(0, 10, "a");
+    let b = model.new_ivar(0, 10, "b");
+    let x = model.geq(b, 6);
+    let y = model.geq(a, 8);
+
+    let constraints = vec![model.lt(a, b), model.or2(x, y)];
+    let theory = IncSTN::new(model.new_write_token());
+    let mut solver = Solver::new(model);
+    solver.add_theory(Box::new(theory));
+    solver.enforce_all(&constraints);
+    assert_eq!(solver.solve(), SolveResult::Sat);
+
+    let record = solver.stats_record();
+    assert_eq!(
+        record.get("decisions"),
+        Some(&StatValue::Int(solver.stats.num_decisions))
+    );
+    assert!(record.get("sat.learnt_clauses").is_some());
+    // counters of the STN, exposed through the `Theory` trait (one node for each of "true", a and b)
+    assert_eq!(record.get("theory1.nodes"), Some(&StatValue::Int(3)));
+    match record.get("theory1.propagations") {
+        Some(&StatValue::Int(n)) => assert!(n > 0),
+        other => panic!("Unexpected value: {:?}", other),
+    }
+
+    assert_eq!(
+        record.csv_header().split(',').count(),
+        record.csv_row().split(',').count()
+    );
+}
+
 #[test]
 fn minimize_small() {
     let mut model = Model::new();
@@ -81,7 +477,7 @@ fn minimize_small() {
 
     solver.add_theory(Box::new(theory));
     solver.enforce_all(&constraints);
-    assert!(solver.solve());
+    assert_eq!(solver.solve(), SolveResult::Sat);
     match solver.minimize(a) {
         None => panic!(),
         Some((val, _)) => assert_eq!(val, 6),
@@ -89,6 +485,347 @@ fn minimize_small() {
     solver.print_stats()
 }
 
+#[test]
+fn minimize_with_strategies() {
+    for &strategy in &[
+        OptimizationStrategy::Linear,
+        OptimizationStrategy::Dichotomic,
+        OptimizationStrategy::LowerBoundLinear,
+    ] {
+        let mut model = Model::new();
+        let a = model.new_ivar(0, 10, "a");
+        let b = model.new_ivar(0, 10, "b");
+        let c = model.new_ivar(0, 10, "c");
+
+        let x = model.geq(b, 6);
+        let y = model.geq(b, 8);
+
+        let constraints = vec![model.lt(a, b), model.lt(b, c), model.lt(a, c), model.or2(x, y)];
+        let theory = IncSTN::new(model.new_write_token());
+        let mut solver = Solver::new(model);
+        solver.add_theory(Box::new(theory));
+        solver.enforce_all(&constraints);
+
+        let mut last_bounds = None;
+        let result = solver.minimize_with_strategy(c, strategy, |bounds, _| {
+            assert!(bounds.lb <= 7 && 7 <= bounds.ub);
+            last_bounds = Some(bounds);
+        });
+        match result {
+            OptimizationResult::Optimal { value, solution } => {
+                assert_eq!(value, 7);
+                assert_eq!(solution.domain_of(c), (7, 7));
+            }
+            _ => panic!("Optimum not found with strategy {}", strategy),
+        }
+        assert_eq!(last_bounds.map(|b| b.ub), Some(7));
+    }
+}
+
+#[test]
+fn minimize_infeasible() {
+    for &strategy in &[
+        OptimizationStrategy::Linear,
+        OptimizationStrategy::Dichotomic,
+        OptimizationStrategy::LowerBoundLinear,
+    ] {
+        let mut model = Model::new();
+        let a = model.new_ivar(0, 10, "a");
+        let b = model.new_ivar(0, 10, "b");
+        let constraints = vec![model.lt(a, b), model.lt(b, a)];
+        let theory = IncSTN::new(model.new_write_token());
+        let mut solver = Solver::new(model);
+        solver.add_theory(Box::new(theory));
+        solver.enforce_all(&constraints);
+
+        let result = solver.minimize_with_strategy(a, strategy, |_, _| ());
+        assert!(matches!(result, OptimizationResult::Infeasible));
+    }
+}
+
+#[test]
+fn search_limits() {
+    let mut model = Model::new();
+    let a = model.new_ivar(0, 10, "a");
+    let b = model.new_ivar(0, 10, "b");
+    let x = model.geq(b, 6);
+    let y = model.leq(b, 2);
+    let constraints = vec![model.lt(a, b), model.or2(x, y)];
+    let theory = IncSTN::new(model.new_write_token());
+    let mut solver = Solver::new(model);
+    solver.add_theory(Box::new(theory));
+    solver.enforce_all(&constraints);
+
+    solver.set_limits(SearchLimits::none().with_max_decisions(0));
+    assert_eq!(solver.solve(), SolveResult::Unknown);
+    let result = solver.minimize_with_strategy(b, OptimizationStrategy::Linear, |_, _| ());
+    assert!(matches!(result, OptimizationResult::Unknown { lower_bound: 1 }));
+
+    solver.set_limits(SearchLimits::none());
+    let interrupt = solver.interrupt_flag();
+    interrupt.store(true, Ordering::Relaxed);
+    assert_eq!(solver.solve(), SolveResult::Unknown);
+    interrupt.store(false, Ordering::Relaxed);
+    assert_eq!(solver.solve(), SolveResult::Sat);
+
+    let result = solver.minimize_with_strategy(b, OptimizationStrategy::Linear, |_, _| ());
+    assert!(matches!(result, OptimizationResult::Optimal { value: 1, .. }));
+}
+
+#[test]
+fn warm_start() {
+    let mut model = Model::new();
+    let a = model.new_ivar(0, 10, "a");
+    let b = model.new_ivar(0, 10, "b");
+    let c = model.new_ivar(0, 10, "c");
+    let x = model.geq(b, 6);
+    let y = model.geq(b, 8);
+    let constraints = vec![model.lt(a, b), model.lt(b, c), model.or2(x, y)];
+
+    // save a solution that is not the first one found
+    let mut solver = build_solver(&model, IncSTN::new, &constraints);
+    let (_, solution) = solver.minimize(c).unwrap();
+    assert_eq!(solution.domain_of(c), (7, 7));
+    let json = LabeledValues::from_solution(&solution, &solution).to_json();
+
+    // as preferred values, the saved ones lead directly to the same solution
+    let values = LabeledValues::from_json(&json).unwrap();
+    let mut solver = build_solver(&model, IncSTN::new, &constraints);
+    assert!(solver.warm_start(&values.resolve(&solver.model).unwrap()));
+    assert_eq!(solver.solve(), SolveResult::Sat);
+    for var in [a, b, c].iter().copied() {
+        assert_eq!(solver.model.domain_of(var), solution.domain_of(var));
+    }
+
+    // the search extending the saved values is subject to the limits of the solver
+    let mut solver = build_solver(&model, IncSTN::new, &constraints);
+    solver.set_limits(SearchLimits::none().with_max_decisions(0));
+    assert!(!solver.warm_start(&values.resolve(&solver.model).unwrap()));
+
+    // as assumptions, the saved values restrict the solutions
+    let mut solver = build_solver(&model, IncSTN::new, &constraints);
+    let assumptions = values.to_assumptions(&solver.model).unwrap();
+    assert_eq!(solver.solve_with_assumptions(&assumptions), SolveResult::Sat);
+    assert_eq!(solver.model.domain_of(c), (7, 7));
+    let conflicting = LabeledValues::from_json(r#"{"a": 5, "c": 6}"#).unwrap();
+    let assumptions = conflicting.to_assumptions(&solver.model).unwrap();
+    assert_eq!(solver.solve_with_assumptions(&assumptions), SolveResult::Unsat);
+}
+
+#[test]
+fn hints() {
+    let mut model = Model::new();
+    let a = model.new_ivar(0, 10, "a");
+    let b = model.new_ivar(0, 10, "b");
+    let end = model.new_ivar(0, 20, "end");
+    // two tasks of duration 3 that cannot overlap
+    let a_first = model.leq(a + 3, b);
+    let b_first = model.leq(b + 3, a);
+    let constraints = vec![
+        model.or2(a_first, b_first),
+        model.leq(a + 3, end),
+        model.leq(b + 3, end),
+    ];
+    let hint = [(VarRef::from(a), 7), (VarRef::from(b), 2)];
+
+    // the first solution follows the hint when the hinted variables are decided first
+    let mut solver = build_solver(&model, IncSTN::new, &constraints);
+    solver.hint(&hint, true);
+    assert_eq!(solver.solve(), SolveResult::Sat);
+    assert_eq!(solver.model.domain_of(a), (7, 7));
+    assert_eq!(solver.model.domain_of(b), (2, 2));
+
+    // the hint only biases the search and does not prevent finding the optimum
+    let mut solver = build_solver(&model, IncSTN::new, &constraints);
+    solver.hint(&hint, true);
+    let mut first = None;
+    let result = solver.minimize_with_strategy(end, OptimizationStrategy::Linear, |bounds, solution| {
+        if solution.is_some() && first.is_none() {
+            first = Some(bounds.ub);
+        }
+    });
+    assert_eq!(first, Some(10));
+    assert!(matches!(result, OptimizationResult::Optimal { value: 6, .. }));
+}
+
+#[derive(Default)]
+struct Events {
+    decisions: u64,
+    conflicts: u64,
+    restarts: u64,
+    solutions: u64,
+    backtracks: u64,
+    objective_bounds: Vec<ObjectiveBounds>,
+}
+
+/// Monitor that records the events of the search and stops it after a given number of solutions.
+struct Recorder {
+    events: Rc<RefCell<Events>>,
+    max_solutions: u64,
+}
+
+impl SearchMonitor for Recorder {
+    fn on_decision(&mut self, _decision: Bound, level: DecLvl, _stats: &Stats) {
+        assert!(level > DecLvl::ROOT);
+        self.events.borrow_mut().decisions += 1;
+    }
+    fn on_conflict(&mut self, learnt: &[Bound], _backtrack_level: DecLvl, _stats: &Stats) {
+        assert!(!learnt.is_empty());
+        self.events.borrow_mut().conflicts += 1;
+    }
+    fn on_restart(&mut self, _stats: &Stats) {
+        self.events.borrow_mut().restarts += 1;
+    }
+    fn on_solution(&mut self, model: &Model, _stats: &Stats) {
+        assert!(model.discrete.variables().all(|v| model.discrete.domains.is_bound(v)));
+        self.events.borrow_mut().solutions += 1;
+    }
+    fn on_objective_bounds(&mut self, bounds: ObjectiveBounds, _solution: Option<&SavedAssignment>, _stats: &Stats) {
+        self.events.borrow_mut().objective_bounds.push(bounds);
+    }
+    fn on_backtrack(&mut self, _level: DecLvl) {
+        self.events.borrow_mut().backtracks += 1;
+    }
+    fn should_stop(&mut self, _stats: &Stats) -> bool {
+        self.events.borrow().solutions >= self.max_solutions
+    }
+}
+
+#[test]
+fn search_monitor() {
+    let mut model = Model::new();
+    let a = model.new_ivar(0, 10, "a");
+    let b = model.new_ivar(0, 10, "b");
+    let c = model.new_ivar(0, 10, "c");
+    let x = model.geq(b, 6);
+    let y = model.geq(b, 8);
+    let constraints = vec![model.lt(a, b), model.lt(b, c), model.or2(x, y)];
+    let monitored_solver = |model: &Model, max_solutions: u64| {
+        let mut solver = build_solver(model, IncSTN::new, &constraints);
+        let events = Rc::new(RefCell::new(Events::default()));
+        solver.add_monitor(Box::new(Recorder {
+            events: events.clone(),
+            max_solutions,
+        }));
+        // start from a non-optimal solution
+        solver.hint(&[(VarRef::from(c), 10)], true);
+        (solver, events)
+    };
+
+    let (mut solver, events) = monitored_solver(&model, u64::MAX);
+    let mut num_solutions = 0;
+    let mut progress = Vec::new();
+    let result = solver.minimize_with_strategy(c, OptimizationStrategy::Linear, |bounds, solution| {
+        if solution.is_some() {
+            num_solutions += 1;
+        }
+        progress.push(bounds);
+    });
+    assert!(matches!(result, OptimizationResult::Optimal { value: 7, .. }));
+    assert_eq!(events.borrow().decisions, solver.stats.num_decisions);
+    assert!(events.borrow().conflicts <= solver.stats.num_conflicts);
+    assert_eq!(events.borrow().solutions, num_solutions);
+    assert!(num_solutions > 1);
+    assert_eq!(events.borrow().objective_bounds, progress);
+    // a new search starts by retracting the decisions of the previous one
+    let backtracks = events.borrow().backtracks;
+    assert_eq!(solver.solve(), SolveResult::Sat);
+    assert_eq!(events.borrow().backtracks, backtracks + 1);
+
+    // the monitor stops the search after the first solution
+    let (mut solver, events) = monitored_solver(&model, 1);
+    let result = solver.minimize_with_strategy(c, OptimizationStrategy::Linear, |_, _| ());
+    assert!(matches!(result, OptimizationResult::Feasible { .. }));
+    assert_eq!(events.borrow().solutions, 1);
+}
+
+#[test]
+fn parallel_portfolio() {
+    let mut model = Model::new();
+    let a = model.new_ivar(0, 10, "a");
+    let b = model.new_ivar(0, 10, "b");
+    let c = model.new_ivar(0, 10, "c");
+    let x = model.geq(b, 6);
+    let y = model.geq(b, 8);
+    let constraints = vec![model.lt(a, b), model.lt(b, c), model.lt(a, c), model.or2(x, y)];
+
+    for &strategy in &[OptimizationStrategy::Linear, OptimizationStrategy::LowerBoundLinear] {
+        let mut solver = ParSolver::new(model.clone(), constraints.clone(), 4);
+        solver.add_theory(|model| Box::new(IncSTN::new(model.new_write_token())));
+        assert_eq!(solver.solve(), SolveResult::Sat);
+        let solution = solver.solution().unwrap();
+        assert!(solution.domain_of(b).0 >= 6);
+
+        let result = solver.minimize_with_strategy(c, strategy, |bounds, _| {
+            assert!(bounds.lb <= 7 && 7 <= bounds.ub);
+        });
+        assert!(matches!(result, OptimizationResult::Optimal { value: 7, .. }));
+        assert_eq!(solver.stats.len(), 4);
+    }
+
+    let unsat = vec![model.lt(a, b), model.lt(b, a)];
+    let mut solver = ParSolver::new(model, unsat, 3);
+    solver.add_theory(|model| Box::new(IncSTN::new(model.new_write_token())));
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+    assert!(solver.solution().is_none());
+}
+
+/// Brancher that sets the first unbound variable to its upper bound.
+struct MaxValue {
+    variables: Vec<VarRef>,
+    num_saved: u32,
+}
+
+impl Brancher for MaxValue {
+    fn next_decision(&mut self, _stats: &Stats, model: &Model) -> Option<Decision> {
+        self.variables
+            .iter()
+            .map(|&v| (v, model.var_domain(v)))
+            .find(|(_, dom)| dom.lb < dom.ub)
+            .map(|(v, dom)| Decision::SetLiteral(Bound::geq(v, dom.ub)))
+    }
+}
+
+impl Backtrack for MaxValue {
+    fn save_state(&mut self) -> DecLvl {
+        self.num_saved += 1;
+        DecLvl::from(self.num_saved)
+    }
+
+    fn num_saved(&self) -> u32 {
+        self.num_saved
+    }
+
+    fn restore_last(&mut self) {
+        self.num_saved -= 1;
+    }
+}
+
+#[test]
+fn custom_brancher() {
+    let mut model = Model::new();
+    let a = model.new_ivar(0, 10, "a");
+    let b = model.new_ivar(0, 10, "b");
+    let c = model.new_ivar(0, 10, "c");
+    let constraints = vec![model.lt(a, b), model.lt(b, c)];
+    let theory = IncSTN::new(model.new_write_token());
+    let brancher = MaxValue {
+        variables: model.discrete.variables().collect(),
+        num_saved: 0,
+    };
+    let mut solver = Solver::with_brancher(model, Box::new(brancher));
+    solver.add_theory(Box::new(theory));
+    solver.enforce_all(&constraints);
+
+    assert_eq!(solver.solve(), SolveResult::Sat);
+    assert_eq!(solver.model.domain_of(a), (8, 8));
+    assert_eq!(solver.model.domain_of(b), (9, 9));
+    assert_eq!(solver.model.domain_of(c), (10, 10));
+
+    let result = solver.minimize_with_strategy(c, OptimizationStrategy::Linear, |_, _| ());
+    assert!(matches!(result, OptimizationResult::Optimal { value: 2, .. }));
+}
+
 #[test]
 fn int_bounds() {
     let mut model = Model::new();
@@ -213,3 +950,345 @@ fn ints_and_bools() {
     assert_eq!(solver.model.domain_of(ia), (1, 1));
     assert_eq!(solver.model.boolean_value_of(a), Some(true));
 }
+
+#[test]
+fn linear_constraints() {
+    let mut model = Model::new();
+    let x = model.new_ivar(0, 10, "x");
+    let y = model.new_ivar(0, 10, "y");
+    let z = model.new_ivar(0, 10, "z");
+    let cost = model.new_ivar(-100, 100, "cost");
+    let big = model.new_bvar("big");
+
+    // 3x + 2y + z >= 17,   x + y + z <= 12,   x - 2z = y - 7,   big <=> 2x + y > 10
+    // cost = 5x + 4y + 2z - 3big
+    let c1 = model.linear_geq(LinearSum::from(x) * 3 + LinearSum::from(y) * 2 + z, 17);
+    let c2 = model.linear_leq(LinearSum::of(vec![x, y, z]), 12);
+    let c3 = model.linear_eq(LinearSum::from(x) - LinearSum::from(z) * 2, LinearSum::from(y) - 7);
+    let gt = model.linear_lt(10, LinearSum::from(x) * 2 + y);
+    let c4 = model.implies(big, gt);
+    let c5 = model.implies(gt, big);
+    let obj =
+        LinearSum::from(x) * 5 + LinearSum::from(y) * 4 + LinearSum::from(z) * 2 - LinearSum::from(IVar::from(big)) * 3;
+    let c6 = model.linear_eq(cost, obj);
+    let constraints = vec![c1, c2, c3, c4, c5, c6];
+
+    let mut solver = build_solver(&model, LinearTheory::new, &constraints);
+    let (optimum, solution) = solver.minimize(cost).unwrap();
+    verify_solution(&model, &constraints, &solution).unwrap();
+
+    // brute force the optimum
+    let mut best = None;
+    for x in 0..=10 {
+        for y in 0..=10 {
+            for z in 0..=10 {
+                if 3 * x + 2 * y + z >= 17 && x + y + z <= 12 && x - 2 * z == y - 7 {
+                    let big = if 2 * x + y > 10 { 1 } else { 0 };
+                    let cost = 5 * x + 4 * y + 2 * z - 3 * big;
+                    best = Some(best.map_or(cost, |b: IntCst| b.min(cost)));
+                }
+            }
+        }
+    }
+    assert_eq!(Some(optimum), best);
+
+    // no solution with a cost below the optimum
+    let mut constraints = constraints;
+    constraints.push(model.linear_lt(cost, optimum));
+    let mut solver = build_solver(&model, LinearTheory::new, &constraints);
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+}
+
+#[test]
+fn linear_difference_constraints() {
+    let mut model = Model::new();
+    let x = model.new_ivar(0, 10, "x");
+    let y = model.new_ivar(0, 10, "y");
+    // x - y <= 3  and  y <= x - 2
+    let c1 = model.linear_leq(LinearSum::from(x) - y, 3);
+    let c2 = model.linear_leq(y, LinearSum::from(x) - 2);
+    let constraints = vec![c1, c2];
+
+    // difference constraints are supported without a difference logic theory
+    let mut solver = build_solver(&model, LinearTheory::new, &constraints);
+    let mut num_solutions = 0;
+    for sol in solver.enumerate(&[x.into(), y.into()]) {
+        verify_solution(&model, &constraints, &sol).unwrap();
+        let (x, y) = (sol.domain_of(x).0, sol.domain_of(y).0);
+        assert!(2 <= x - y && x - y <= 3);
+        num_solutions += 1;
+    }
+    // x - y = 2 for x in [2, 10],  x - y = 3 for x in [3, 10]
+    assert_eq!(num_solutions, 9 + 8);
+}
+
+#[test]
+fn boolean_eq_and_xor() {
+    let mut model = Model::new();
+    let a = model.new_bvar("a");
+    let b = model.new_bvar("b");
+    let c = model.new_bvar("c");
+    let d = model.new_bvar("d");
+    let (la, lb): (BAtom, BAtom) = (a.into(), b.into());
+
+    // normalization of equivalent expressions
+    assert_eq!(model.xor2(la, lb), model.xor2(lb, la));
+    assert_eq!(model.xor2(!la, lb), !model.xor2(la, lb));
+    assert_eq!(model.xor2(la, la), BAtom::Cst(false));
+    assert_eq!(model.xor2(la, !la), BAtom::Cst(true));
+    assert_eq!(model.eq(la, true), la);
+    assert_eq!(model.eq(la, lb), !model.xor2(la, lb));
+
+    // d <=> (b or c),   a xor b xor c,   a <=> !d
+    let b_or_c = model.or2(b.into(), c.into());
+    let constraints = vec![
+        model.eq(BAtom::from(d), b_or_c),
+        model.xor(&[a.into(), b.into(), c.into()]),
+        model.bool_eq(a, !d),
+    ];
+
+    let mut solver = Solver::new(model.clone());
+    solver.enforce_all(&constraints);
+    let mut solutions = HashSet::new();
+    for sol in solver.enumerate(&[a.into(), b.into(), c.into(), d.into()]) {
+        verify_solution(&model, &constraints, &sol).unwrap();
+        let value = |v: aries_model::lang::BVar| sol.boolean_value_of(v).unwrap();
+        solutions.insert((value(a), value(b), value(c), value(d)));
+    }
+    let mut expected = HashSet::new();
+    for &a in &[false, true] {
+        for &b in &[false, true] {
+            for &c in &[false, true] {
+                let d = b || c;
+                if a ^ b ^ c && a != d {
+                    expected.insert((a, b, c, d));
+                }
+            }
+        }
+    }
+    assert!(!expected.is_empty());
+    assert_eq!(solutions, expected);
+}
+
+#[test]
+fn max_constraint() {
+    let mut model = Model::new();
+    let a = model.new_ivar(0, 10, "a");
+    let b = model.new_ivar(0, 10, "b");
+    let c = model.new_ivar(0, 10, "c");
+    let makespan = model.new_ivar(0, 20, "makespan");
+    let constraints = vec![
+        model.eq_max(makespan, &[a, b, c]),
+        model.geq(a, 3),
+        model.leq(a + 2, b),
+        model.leq(c, 4),
+    ];
+
+    let mut solver = Solver::new(model.clone());
+    let csp = CSP::new(solver.model.new_write_token());
+    solver.add_theory(Box::new(csp));
+    let stn = IncSTN::new(solver.model.new_write_token());
+    solver.add_theory(Box::new(stn));
+    solver.enforce_all(&constraints);
+
+    let (optimum, solution) = solver.minimize(makespan).unwrap();
+    assert_eq!(optimum, 5);
+    verify_solution(&model, &constraints, &solution).unwrap();
+}
+
+#[test]
+fn negated_max_constraint() {
+    let mut model = Model::new();
+    let a = model.new_ivar(0, 2, "a");
+    let b = model.new_ivar(0, 2, "b");
+    let m = model.new_ivar(0, 2, "m");
+    let max = model.eq_max(m, &[a, b]);
+    let constraints = vec![!max];
+
+    let mut solver = build_solver(&model, CSP::new, &constraints);
+
+    let mut solutions = HashSet::new();
+    for sol in solver.enumerate(&[a.into(), b.into(), m.into()]) {
+        verify_solution(&model, &constraints, &sol).unwrap();
+        solutions.insert((sol.domain_of(a).0, sol.domain_of(b).0, sol.domain_of(m).0));
+    }
+    // all assignments except the 9 ones where m = max(a, b)
+    assert_eq!(solutions.len(), 27 - 9);
+    assert!(solutions.iter().all(|&(a, b, m)| m != a.max(b)));
+}
+
+#[test]
+fn all_different() {
+    // n-queens: one queen per column, with distinct rows and diagonals
+    let n_queens = |n: IntCst| {
+        let mut model = Model::new();
+        let queens: Vec<IVar> = (0..n).map(|i| model.new_ivar(0, n - 1, format!("q{}", i))).collect();
+        let rows: Vec<IAtom> = queens.iter().map(|&q| q.into()).collect();
+        let diags1: Vec<IAtom> = queens.iter().zip(0..n).map(|(&q, i)| q + i).collect();
+        let diags2: Vec<IAtom> = queens.iter().zip(0..n).map(|(&q, i)| q - i).collect();
+        let constraints = vec![
+            model.all_different(&rows),
+            model.all_different(&diags1),
+            model.all_different(&diags2),
+        ];
+
+        let mut solver = build_solver(&model, CSP::new, &constraints);
+        let vars: Vec<VarRef> = queens.iter().map(|&q| q.into()).collect();
+        let mut num_solutions = 0;
+        for sol in solver.enumerate(&vars) {
+            verify_solution(&model, &constraints, &sol).unwrap();
+            num_solutions += 1;
+        }
+        num_solutions
+    };
+    assert_eq!(n_queens(3), 0);
+    assert_eq!(n_queens(4), 2);
+    assert_eq!(n_queens(5), 10);
+    assert_eq!(n_queens(6), 4);
+    assert_eq!(n_queens(8), 92);
+
+    // pigeon hole, with one of the pigeons optional
+    let mut model = Model::new();
+    let pigeons: Vec<IAtom> = (0..4).map(|i| model.new_ivar(0, 2, format!("p{}", i)).into()).collect();
+    let all = model.all_different(&pigeons);
+    let three = model.all_different(&pigeons[0..3]);
+    let with_constant = model.all_different(&[pigeons[0], pigeons[1], 2.into()]);
+    let mut solver = build_solver(&model, CSP::new, &[three, with_constant]);
+    assert_eq!(solver.solve(), SolveResult::Sat);
+    assert_eq!(solver.model.domain_of(pigeons[2]), (2, 2));
+    solver.reset();
+    solver.enforce(all);
+    assert_eq!(solver.solve(), SolveResult::Unsat);
+}
+
+#[test]
+fn table_constraint() {
+    let mut model = Model::new();
+    let x = model.new_ivar(0, 4, "x");
+    let y = model.new_ivar(0, 4, "y");
+    let z = model.new_ivar(0, 4, "z");
+    #[rustfmt::skip]
+    let lines: Vec<[IntCst; 3]> = vec![
+        [0, 1, 2],
+        [1, 1, 3],
+        [1, 3, 2],
+        [2, 0, 5],
+        [3, 4, 1],
+        [4, 2, 3],
+    ];
+    // (x, y + 1, z) is in the table and (x, z) is not one of (1, 2) and (3, 3)
+    let in_table = model.in_table(&[x.into(), y + 1, z.into()], lines.iter().map(|l| &l[..]));
+    let forbidden: Vec<[IntCst; 2]> = vec![[1, 2], [3, 3]];
+    let not_forbidden = !model.in_table(&[x.into(), z.into()], forbidden.iter().map(|l| &l[..]));
+    let constraints = vec![in_table, not_forbidden];
+
+    let mut solver = build_solver(&model, CSP::new, &constraints);
+
+    let mut solutions = HashSet::new();
+    for sol in solver.enumerate(&[x.into(), y.into(), z.into()]) {
+        verify_solution(&model, &constraints, &sol).unwrap();
+        solutions.insert([sol.domain_of(x).0, sol.domain_of(y).0 + 1, sol.domain_of(z).0]);
+    }
+    let expected: HashSet<[IntCst; 3]> = lines
+        .iter()
+        .copied()
+        .filter(|l| l[1] >= 1 && l.iter().all(|&v| v <= 4))
+        .filter(|l| !forbidden.contains(&[l[0], l[2]]))
+        .collect();
+    assert_eq!(solutions, expected);
+}
+
+#[test]
+fn element_constraint() {
+    let mut model = Model::new();
+    let index = model.new_ivar(-1, 5, "index");
+    let a = model.new_ivar(0, 3, "a");
+    let b = model.new_ivar(2, 4, "b");
+    let value = model.new_ivar(1, 3, "value");
+    let array = [a.into(), 4.into(), b.into(), 1.into()];
+    let elem = model.element(index, &array, value);
+    let constraints = vec![elem];
+
+    let mut solver = build_solver(&model, CSP::new, &constraints);
+
+    let mut solutions = HashSet::new();
+    for sol in solver.enumerate(&[index.into(), a.into(), b.into(), value.into()]) {
+        verify_solution(&model, &constraints, &sol).unwrap();
+        let values = [
+            sol.domain_of(index).0,
+            sol.domain_of(a).0,
+            sol.domain_of(b).0,
+            sol.domain_of(value).0,
+        ];
+        solutions.insert(values);
+    }
+    let mut expected = HashSet::new();
+    for i in -1..=5 {
+        for a in 0..=3 {
+            for b in 2..=4 {
+                for v in 1..=3 {
+                    let array = [a, 4, b, 1];
+                    if (0..4).contains(&i) && array[i as usize] == v {
+                        expected.insert([i, a, b, v]);
+                    }
+                }
+            }
+        }
+    }
+    assert_eq!(solutions, expected);
+
+    // the negation of the constraint holds for the remaining assignments, including all out-of-range indices
+    let mut solver = build_solver(&model, CSP::new, &[!elem]);
+    let num_solutions = solver
+        .enumerate(&[index.into(), a.into(), b.into(), value.into()])
+        .count();
+    assert_eq!(num_solutions, 7 * 4 * 3 * 3 - expected.len());
+}
+
+#[test]
+fn element_with_shared_variables() {
+    let mut model = Model::new();
+    let x: Vec<IVar> = (0..3).map(|i| model.new_ivar(0, 3, format!("x{}", i))).collect();
+    let vars: Vec<VarRef> = x.iter().map(|&v| v.into()).collect();
+    // x0 is both the index and the value
+    let shared_index = model.element(x[0], &[x[1].into(), 3.into(), x[2].into()], x[0]);
+    // x1 appears twice in the array and is also the value
+    let shared_elements = model.element(x[0], &[x[1].into(), x[2].into(), x[1] + 1], x[1]);
+    // x0 is both the index and an element of the array
+    let index_in_array = model.element(x[0], &[x[1].into(), x[0].into(), 2.into()], x[2]);
+
+    let count = |constraint: BAtom, holds: &dyn Fn(&[IntCst]) -> bool| {
+        let mut solver = build_solver(&model, CSP::new, &[constraint]);
+        let mut num_solutions = 0;
+        for sol in solver.enumerate(&vars) {
+            verify_solution(&model, &[constraint], &sol).unwrap();
+            let values: Vec<IntCst> = x.iter().map(|&v| sol.domain_of(v).0).collect();
+            assert!(holds(&values));
+            num_solutions += 1;
+        }
+        let mut expected = 0;
+        for i in 0..64 {
+            if holds(&[i % 4, (i / 4) % 4, i / 16]) {
+                expected += 1;
+            }
+        }
+        assert_eq!(num_solutions, expected);
+    };
+    count(shared_index, &|x| match x[0] {
+        0 => x[1] == 0,
+        1 => false,
+        2 => x[2] == 2,
+        _ => false,
+    });
+    count(shared_elements, &|x| match x[0] {
+        0 => true,
+        1 => x[2] == x[1],
+        _ => false,
+    });
+    count(index_in_array, &|x| match x[0] {
+        0 => x[2] == x[1],
+        1 => x[2] == 1,
+        2 => x[2] == 2,
+        _ => false,
+    });
+}
@@ -5,8 +5,11 @@
 # in the problems/cnf/{sat.zip, unsat.zip} archives.
 
 import os
+import shutil
 import subprocess
 import sys
+import tempfile
+import zipfile
 
 if len(sys.argv) < 2 or sys.argv[1] == "release":
     os.system("cargo build --release --bin aries-sat")
@@ -21,6 +24,9 @@ else:
 
 solver_cmd = solver + " {params} --source {archive} {instance}"
 
+# if available, drat-trim is used to check the proofs of unsatisfiability
+drat_trim = shutil.which("drat-trim")
+
 
 def files_in_archive(archive):
     res = subprocess.run(["zipinfo", "-1", str(archive)], stdout=subprocess.PIPE, universal_newlines=True)
@@ -38,11 +44,24 @@ def run_all(archive, sat):
             print("Solving UNSAT:    " + str(instance))
             params = "--sat false"
 
-        cmd = solver_cmd.format(params=params, archive=archive, instance=instance).split(" ")
-        solver_run = subprocess.run(cmd, stdout=subprocess.PIPE, universal_newlines=True)
-        if solver_run.returncode != 0:
-            print("Solver did not return expected result")
-            exit(1)
+        with tempfile.TemporaryDirectory() as tmp_dir:
+            proof = os.path.join(tmp_dir, "proof.drat")
+            if not sat and drat_trim:
+                params += " --proof " + proof
+
+            cmd = solver_cmd.format(params=params, archive=archive, instance=instance).split(" ")
+            solver_run = subprocess.run(cmd, stdout=subprocess.PIPE, universal_newlines=True)
+            if solver_run.returncode != 0:
+                print("Solver did not return expected result")
+                exit(1)
+
+            if not sat and drat_trim:
+                with zipfile.ZipFile(archive) as z:
+                    cnf = z.extract(instance, tmp_dir)
+                check = subprocess.run([drat_trim, cnf, proof], stdout=subprocess.PIPE, universal_newlines=True)
+                if "s VERIFIED" not in check.stdout:
+                    print("Invalid proof of unsatisfiability")
+                    exit(1)
 
 
 run_all("sat/instances/test-sat.zip", sat=True)
@@ -5,6 +5,9 @@ use crate::symbols::SymId;
 use crate::symbols::{ContiguousSymbols, SymbolTable};
 use crate::Model;
 
+mod labeled_values;
+pub use labeled_values::*;
+
 pub trait Assignment {
     fn symbols(&self) -> &SymbolTable;
 
@@ -0,0 +1,156 @@
+use crate::assignments::{Assignment, SavedAssignment};
+use crate::bounds::Bound;
+use crate::lang::{IntCst, VarRef};
+use crate::Model;
+use anyhow::*;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Values of variables in a solution, identified by their labels so that they can be persisted and later
+/// reloaded into another model of the same problem (where the variables might be numbered differently).
+///
+/// The values are (de)serialized as a JSON object mapping each label to the value of its variable, e.g.,
+/// `{"makespan": 55, "start(0, 0)": 0}`. Booleans are represented by 0 (false) and 1 (true).
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LabeledValues {
+    pub values: BTreeMap<String, IntCst>,
+}
+
+impl LabeledValues {
+    /// Extracts the values of all labeled variables of `model` that are bound in `solution`.
+    ///
+    /// Variables that are unlabeled or that share their label with another variable are ignored,
+    /// as they could not be identified when importing the values.
+    pub fn from_solution(model: &Model, solution: &SavedAssignment) -> Self {
+        let ambiguous = ambiguous_labels(model);
+        let mut values = BTreeMap::new();
+        for var in model.discrete.variables() {
+            if let Some(label) = model.discrete.label(var) {
+                let domain = solution.var_domain(var);
+                if domain.is_bound() && !ambiguous.contains(label) {
+                    values.insert(label.to_string(), domain.lb);
+                }
+            }
+        }
+        LabeledValues { values }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Serialization of a map of integers cannot fail")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("Invalid JSON assignment")
+    }
+
+    pub fn write_to_file(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_json()).with_context(|| format!("Cannot write {}", path.display()))
+    }
+
+    pub fn read_from_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+        Self::from_json(&json).with_context(|| format!("In {}", path.display()))
+    }
+
+    /// Associates each value to the variable of `model` that has its label.
+    /// Returns an error if a label does not identify exactly one variable of the model.
+    pub fn resolve(&self, model: &Model) -> Result<Vec<(VarRef, IntCst)>> {
+        let mut vars: HashMap<&str, Option<VarRef>> = HashMap::new();
+        for var in model.discrete.variables() {
+            if let Some(label) = model.discrete.label(var) {
+                vars.entry(label)
+                    .and_modify(|previous| *previous = None)
+                    .or_insert(Some(var));
+            }
+        }
+        self.values
+            .iter()
+            .map(|(label, &value)| match vars.get(label.as_str()) {
+                Some(Some(var)) => Ok((*var, value)),
+                Some(None) => bail!("Label \"{}\" is shared by several variables", label),
+                None => bail!("No variable with label \"{}\"", label),
+            })
+            .collect()
+    }
+
+    /// Returns the literals that fix each variable of `model` to its value.
+    /// They can be passed as assumptions to the solver to restrict the search to the solutions that agree
+    /// with these values.
+    pub fn to_assumptions(&self, model: &Model) -> Result<Vec<Bound>> {
+        let mut assumptions = Vec::with_capacity(self.values.len() * 2);
+        for (var, value) in self.resolve(model)? {
+            assumptions.push(Bound::geq(var, value));
+            assumptions.push(Bound::leq(var, value));
+        }
+        Ok(assumptions)
+    }
+}
+
+/// Returns the labels that are given to more than one variable of the model.
+fn ambiguous_labels(model: &Model) -> HashSet<&str> {
+    let mut seen = HashSet::new();
+    let mut ambiguous = HashSet::new();
+    for var in model.discrete.variables() {
+        if let Some(label) = model.discrete.label(var) {
+            if !seen.insert(label) {
+                ambiguous.insert(label);
+            }
+        }
+    }
+    ambiguous
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aries_backtrack::Backtrack;
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut model = Model::new();
+        let a = model.new_bvar("a");
+        let x = model.new_ivar(0, 10, "x");
+        let y = model.new_ivar(0, 10, "y");
+        let _unlabeled = model.new_ivar(0, 10, "");
+        let dup1 = model.new_ivar(0, 10, "dup");
+        let _dup2 = model.new_ivar(0, 10, "dup");
+
+        let mut solution = model.clone();
+        solution.save_state();
+        solution.discrete.decide(a.true_lit()).unwrap();
+        solution.discrete.decide(Bound::leq(x, 3)).unwrap();
+        solution.discrete.decide(Bound::geq(x, 3)).unwrap();
+        solution.discrete.decide(Bound::leq(dup1, 2)).unwrap();
+        solution.discrete.decide(Bound::geq(dup1, 2)).unwrap();
+
+        // y is not bound and "dup" is ambiguous, the only other variable is the constant "true" of the model
+        let values = LabeledValues::from_solution(&model, &solution);
+        let json = values.to_json();
+        let parsed = LabeledValues::from_json(&json).unwrap();
+        assert_eq!(parsed, values);
+        assert_eq!(parsed.values.len(), 3);
+        assert_eq!(parsed.values["a"], 1);
+        assert_eq!(parsed.values["x"], 3);
+
+        let resolved = parsed.resolve(&model).unwrap();
+        assert!(resolved.contains(&(a.into(), 1)));
+        assert!(resolved.contains(&(x.into(), 3)));
+        let assumptions = parsed.to_assumptions(&model).unwrap();
+        assert!(assumptions.contains(&Bound::geq(x, 3)));
+        assert!(assumptions.contains(&Bound::leq(x, 3)));
+
+        assert!(LabeledValues::from_json("{\"dup\": 2}")
+            .unwrap()
+            .resolve(&model)
+            .is_err());
+        assert!(LabeledValues::from_json("{\"z\": 2}").unwrap().resolve(&model).is_err());
+        assert!(LabeledValues::from_json("[1, 2]").is_err());
+        assert_eq!(
+            LabeledValues::from_json("{\"y\": 4}").unwrap().resolve(&model).unwrap(),
+            vec![(y.into(), 4)]
+        );
+    }
+}
@@ -3,7 +3,7 @@ use crate::lang::VarRef;
 
 /// Represents the upped or the lower bound of a particular variable.
 /// The type has dense integer values and can by used an index in an array.
-#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
 pub struct VarBound(u32);
 
 impl VarBound {
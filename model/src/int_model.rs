@@ -3,7 +3,7 @@ mod explanation;
 
 pub use explanation::*;
 
-use crate::bounds::{Bound, Disjunction, Relation};
+use crate::bounds::{Bound, Disjunction, Relation, VarBound};
 use crate::expressions::ExprHandle;
 use crate::int_model::domains::{Domains, Event};
 use crate::lang::{BVar, IntCst, VarRef};
@@ -12,7 +12,7 @@ use aries_backtrack::DecLvl;
 use aries_backtrack::{Backtrack, DecisionLevelClass, EventIndex, ObsTrail};
 use aries_collections::ref_store::{RefMap, RefVec};
 use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
 #[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
 pub struct IntDomain {
@@ -280,6 +280,175 @@ impl DiscreteModel {
         }
     }
 
+    /// Minimizes a clause produced by `refine_explanation` by removing its redundant literals (MiniSat-style
+    /// recursive minimization).
+    /// A literal of the clause is redundant if its negation is implied by the negation of other literals of the clause.
+    /// This is determined by recursively asking the explainer for the causes of the events that falsified it.
+    ///
+    /// The method must be called before backtracking, while all literals of the clause are still falsified.
+    /// Literals falsified at the current decision level are always kept.
+    pub fn minimize_clause(&mut self, clause: Disjunction, explainer: &mut impl Explainer) -> Disjunction {
+        let clause: Vec<Bound> = clause.into();
+        let current_level = self.domains.current_decision_level();
+
+        // negation of each literal in the clause, indexed by its affected bound.
+        // A disjunction has at most one literal on each affected bound.
+        let mut in_clause: HashMap<VarBound, Bound> = HashMap::with_capacity(clause.len());
+        // decision levels of the literals of the clause
+        let mut levels: HashSet<DecLvl> = HashSet::new();
+        for &l in &clause {
+            debug_assert!(self.entails(!l));
+            in_clause.insert((!l).affected_bound(), !l);
+            levels.insert(self.entailing_level(!l));
+        }
+
+        let mut cache = HashMap::new();
+        let mut minimized = Vec::with_capacity(clause.len());
+        for l in clause {
+            let lvl = self.entailing_level(!l);
+            if lvl == DecLvl::ROOT {
+                // always false, no need to keep it
+                continue;
+            }
+            if lvl == current_level || !self.is_redundant(!l, &in_clause, &levels, &mut cache, explainer) {
+                minimized.push(l);
+            }
+        }
+        Disjunction::new(minimized)
+    }
+
+    /// Returns true if the (entailed) literal `lit` is implied by the literals in `in_clause`.
+    ///
+    /// To avoid circular reasoning, a literal can only be justified by literals made true by strictly earlier events.
+    /// Results are stored in `cache` to be reused for the other literals of the clause.
+    fn is_redundant(
+        &mut self,
+        lit: Bound,
+        in_clause: &HashMap<VarBound, Bound>,
+        levels: &HashSet<DecLvl>,
+        cache: &mut HashMap<Bound, bool>,
+        explainer: &mut impl Explainer,
+    ) -> bool {
+        if let Some(&redundant) = cache.get(&lit) {
+            return redundant;
+        }
+        let mut explanation = Explanation::new();
+        let mut stack = vec![lit];
+        // literals that are implied by the clause if the search succeeds
+        let mut visited = HashSet::new();
+        visited.insert(lit);
+
+        while let Some(l) = stack.pop() {
+            let event = self.implying_event(l).unwrap();
+            let redundant = match self.get_event(event).cause {
+                Cause::Decision => false,
+                Cause::Inference(cause) => {
+                    explanation.clear();
+                    explainer.explain(cause, l, self, &mut explanation);
+                    explanation.literals().iter().all(|&antecedent| {
+                        if !self.entails(antecedent) {
+                            return false;
+                        }
+                        let antecedent_event = match self.implying_event(antecedent) {
+                            Some(ev) => ev,
+                            None => return true, // entailed in the initial state
+                        };
+                        if antecedent_event >= event {
+                            return false;
+                        }
+                        let lvl = self.trail().decision_level(antecedent_event);
+                        if lvl == DecLvl::ROOT {
+                            return true;
+                        }
+                        if let Some(&in_clause) = in_clause.get(&antecedent.affected_bound()) {
+                            if in_clause.entails(antecedent) && self.implying_event(in_clause).unwrap() < event {
+                                return true;
+                            }
+                        }
+                        match cache.get(&antecedent) {
+                            Some(&redundant) => redundant,
+                            // an antecedent from a decision level that does not appear in the clause
+                            // cannot be implied by it
+                            None if !levels.contains(&lvl) => false,
+                            None => {
+                                if visited.insert(antecedent) {
+                                    stack.push(antecedent);
+                                }
+                                true
+                            }
+                        }
+                    })
+                }
+            };
+            if !redundant {
+                cache.insert(l, false);
+                cache.insert(lit, false);
+                return false;
+            }
+        }
+        for l in visited {
+            cache.insert(l, true);
+        }
+        true
+    }
+
+    /// Returns the decisions that imply the literals of the given explanation.
+    ///
+    /// Unlike `refine_explanation`, the analysis does not stop at the current decision level but explains
+    /// all inferred literals until only decisions (and literals entailed at the root level) remain.
+    ///
+    /// As a side effect, the events up to the earliest of these decisions are undone, leaving the model in
+    /// a partially backtracked state. The caller is responsible for backtracking to a consistent decision level.
+    pub fn implying_decisions(&mut self, explanation: Explanation, explainer: &mut impl Explainer) -> Vec<Bound> {
+        let mut explanation = explanation;
+        self.queue.clear();
+        let mut decisions: Vec<Bound> = Vec::new();
+
+        loop {
+            for l in explanation.lits.drain(..) {
+                debug_assert!(self.entails(l));
+                if let Some(loc) = self.implying_event(l) {
+                    if self.trail().decision_level(loc) != DecLvl::ROOT {
+                        self.queue.push(InQueueLit { cause: loc, lit: l })
+                    }
+                }
+            }
+            // select latest implied literal from queue
+            let mut l = match self.queue.pop() {
+                Some(l) => l,
+                None => return decisions,
+            };
+            // skip other references to the same event, they are necessarily contiguous in the queue
+            while let Some(next) = self.queue.peek() {
+                if next.cause == l.cause {
+                    let l2 = self.queue.pop().unwrap();
+                    if l2.lit.entails(l.lit) {
+                        l = l2;
+                    }
+                } else {
+                    break;
+                }
+            }
+
+            // undo all decision levels above the one of the event, and then the events of its own decision level
+            let decision_level = self.trail().decision_level(l.cause);
+            while self.domains.current_decision_level() > decision_level {
+                self.domains.restore_last();
+            }
+            let event = self.get_event(l.cause).new_literal();
+            let mut cause = None;
+            while l.cause < self.domains.trail().next_slot() {
+                cause = Some(self.domains.undo_last_event());
+            }
+            match cause.unwrap() {
+                Cause::Decision => decisions.push(event),
+                Cause::Inference(cause) => {
+                    explainer.explain(cause, l.lit, self, &mut explanation);
+                }
+            }
+        }
+    }
+
     pub fn entails(&self, lit: Bound) -> bool {
         self.domains.entails(lit)
     }
@@ -424,6 +593,7 @@ impl PartialOrd for InQueueLit {
 mod tests {
     use crate::assignments::Assignment;
     use crate::bounds::Bound as ILit;
+    use crate::bounds::Disjunction;
     use crate::int_model::explanation::{Explainer, Explanation};
     use crate::int_model::{Cause, DiscreteModel, EmptyDomain, InferenceCause};
     use crate::lang::{BVar, IVar};
@@ -539,4 +709,56 @@ mod tests {
         expected.insert(ILit::gt(n, 4));
         assert_eq!(clause, expected);
     }
+
+    #[test]
+    fn test_clause_minimization() {
+        let mut model = Model::new();
+        let a = model.new_bvar("a");
+        let b = model.new_bvar("b");
+        let c = model.new_bvar("c");
+        let d = model.new_bvar("d");
+        let e = model.new_bvar("e");
+
+        // constraint 0: "a => b"
+        // constraint 1: "e => d"
+        let writer = WriterId::new(1);
+        struct Expl {
+            a: BVar,
+            e: BVar,
+        }
+        impl Explainer for Expl {
+            fn explain(
+                &mut self,
+                cause: InferenceCause,
+                _literal: ILit,
+                _model: &DiscreteModel,
+                explanation: &mut Explanation,
+            ) {
+                match cause.payload {
+                    0 => explanation.push(self.a.true_lit()),
+                    1 => explanation.push(self.e.true_lit()),
+                    _ => panic!("unexpected payload"),
+                }
+            }
+        }
+        let mut explainer = Expl { a, e };
+
+        // first decision level: a, e and their consequences b, d
+        model.save_state();
+        model.discrete.decide(a.true_lit()).unwrap();
+        model.discrete.decide(e.true_lit()).unwrap();
+        model.discrete.set_lb(b, 1, Cause::inference(writer, 0u32)).unwrap();
+        model.discrete.set_lb(d, 1, Cause::inference(writer, 1u32)).unwrap();
+        // second decision level: c
+        model.save_state();
+        model.discrete.decide(c.true_lit()).unwrap();
+
+        // !b is redundant because b is implied by a, which is part of the clause.
+        // !d must be kept, because it is implied by e that does not appear in the clause.
+        let clause = Disjunction::new(vec![a.false_lit(), b.false_lit(), c.false_lit(), d.false_lit()]);
+        let clause = model.discrete.minimize_clause(clause, &mut explainer);
+        let clause: HashSet<_> = clause.literals().iter().copied().collect();
+        let expected: HashSet<_> = vec![a.false_lit(), c.false_lit(), d.false_lit()].into_iter().collect();
+        assert_eq!(clause, expected);
+    }
 }
@@ -30,6 +30,11 @@ impl Explanation {
         &self.lits
     }
 }
+impl Extend<Bound> for Explanation {
+    fn extend<T: IntoIterator<Item = Bound>>(&mut self, iter: T) {
+        self.lits.extend(iter)
+    }
+}
 impl Default for Explanation {
     fn default() -> Self {
         Self::new()
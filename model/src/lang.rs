@@ -2,6 +2,7 @@ mod atom;
 mod boolean;
 mod expr;
 mod int;
+mod linear;
 mod sym;
 mod variables;
 
@@ -42,6 +43,7 @@ pub use atom::Atom;
 pub use boolean::{BAtom, BExpr, BVar};
 pub use expr::{Expr, Fun};
 pub use int::{IAtom, IVar};
+pub use linear::{LinearLeq, LinearSum, LinearTerm};
 
 use crate::bounds::Bound;
 use crate::types::TypeId;
@@ -8,6 +8,17 @@ pub enum Fun {
     Eq,
     Leq,
     Max,
+    /// Linear constraint `sum(f_i * x_i) <= ub`, with arguments `[ub, f_1, x_1, f_2, x_2, ...]` (see `LinearLeq`).
+    Linear,
+    /// Exclusive or of two boolean atoms.
+    Xor,
+    /// All integer arguments take distinct values.
+    AllDifferent,
+    /// Arguments `[n, x_1, ..., x_n, v_11, ..., v_1n, v_21, ...]`: the values of the `n` variables form one of the
+    /// lines `[v_i1, ..., v_in]` of the table.
+    Table,
+    /// Arguments `[index, value, a_0, a_1, ...]`: the array `[a_0, a_1, ...]` has the given value at the given index.
+    Element,
 }
 
 impl std::fmt::Display for Fun {
@@ -20,6 +31,11 @@ impl std::fmt::Display for Fun {
                 Fun::Eq => "=",
                 Fun::Leq => "<=",
                 Fun::Max => "max",
+                Fun::Linear => "linear",
+                Fun::Xor => "xor",
+                Fun::AllDifferent => "all-different",
+                Fun::Table => "table",
+                Fun::Element => "element",
             }
         )
     }
@@ -0,0 +1,222 @@
+use crate::lang::{Atom, ConversionError, Expr, Fun, IAtom, IVar, IntCst};
+use std::convert::TryFrom;
+
+/// A term `factor * var` of a linear sum.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct LinearTerm {
+    pub factor: IntCst,
+    pub var: IVar,
+}
+
+/// A linear expression `sum(factor_i * var_i) + constant`.
+///
+/// # Usage
+/// ```
+/// use aries_model::Model;
+/// use aries_model::lang::LinearSum;
+/// let mut model = Model::new();
+/// let x = model.new_ivar(0, 10, "x");
+/// let y = model.new_ivar(0, 10, "y");
+/// // 2x + 3y - 1
+/// let sum = LinearSum::from(x) * 2 + LinearSum::from(y) * 3 - 1;
+/// let constraint = model.linear_leq(sum, 12);
+/// ```
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct LinearSum {
+    terms: Vec<LinearTerm>,
+    constant: IntCst,
+}
+
+impl LinearSum {
+    pub fn zero() -> LinearSum {
+        LinearSum::default()
+    }
+
+    pub fn constant(value: IntCst) -> LinearSum {
+        LinearSum {
+            terms: Vec::new(),
+            constant: value,
+        }
+    }
+
+    /// The sum of all the given atoms.
+    pub fn of<T: Into<IAtom>>(atoms: Vec<T>) -> LinearSum {
+        let mut sum = LinearSum::zero();
+        for atom in atoms {
+            sum.add_term(1, atom);
+        }
+        sum
+    }
+
+    /// Adds `factor * atom` to the sum.
+    pub fn add_term(&mut self, factor: IntCst, atom: impl Into<IAtom>) {
+        let atom = atom.into();
+        if let Some(var) = atom.var {
+            self.terms.push(LinearTerm { factor, var });
+        }
+        self.constant += factor * atom.shift;
+    }
+
+    /// Returns the terms of the sum, with at most one term per variable and no null factor.
+    pub fn terms(&self) -> Vec<LinearTerm> {
+        let mut terms = self.terms.clone();
+        terms.sort_by_key(|t| t.var);
+        let mut merged: Vec<LinearTerm> = Vec::with_capacity(terms.len());
+        for t in terms {
+            match merged.last_mut() {
+                Some(last) if last.var == t.var => last.factor += t.factor,
+                _ => merged.push(t),
+            }
+        }
+        merged.retain(|t| t.factor != 0);
+        merged
+    }
+
+    pub fn get_constant(&self) -> IntCst {
+        self.constant
+    }
+}
+
+impl From<IAtom> for LinearSum {
+    fn from(atom: IAtom) -> Self {
+        let mut sum = LinearSum::zero();
+        sum.add_term(1, atom);
+        sum
+    }
+}
+impl From<IVar> for LinearSum {
+    fn from(var: IVar) -> Self {
+        LinearSum::from(IAtom::from(var))
+    }
+}
+impl From<IntCst> for LinearSum {
+    fn from(value: IntCst) -> Self {
+        LinearSum::constant(value)
+    }
+}
+
+impl<T: Into<LinearSum>> std::ops::Add<T> for LinearSum {
+    type Output = LinearSum;
+
+    fn add(mut self, rhs: T) -> Self::Output {
+        let rhs = rhs.into();
+        self.terms.extend_from_slice(&rhs.terms);
+        self.constant += rhs.constant;
+        self
+    }
+}
+impl<T: Into<LinearSum>> std::ops::Sub<T> for LinearSum {
+    type Output = LinearSum;
+
+    fn sub(self, rhs: T) -> Self::Output {
+        self + (-rhs.into())
+    }
+}
+impl std::ops::Neg for LinearSum {
+    type Output = LinearSum;
+
+    fn neg(self) -> Self::Output {
+        self * -1
+    }
+}
+impl std::ops::Mul<IntCst> for LinearSum {
+    type Output = LinearSum;
+
+    fn mul(mut self, factor: IntCst) -> Self::Output {
+        for t in &mut self.terms {
+            t.factor *= factor;
+        }
+        self.constant *= factor;
+        self
+    }
+}
+
+/// The linear constraint `sum(factor_i * var_i) <= upper_bound`, on distinct variables.
+///
+/// It is represented in a model by an expression `(linear ub f1 x1 f2 x2 ...)` of the function [Fun::Linear].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct LinearLeq {
+    pub terms: Vec<LinearTerm>,
+    pub upper_bound: IntCst,
+}
+
+impl LinearLeq {
+    pub fn new(terms: Vec<LinearTerm>, upper_bound: IntCst) -> LinearLeq {
+        LinearLeq { terms, upper_bound }
+    }
+
+    /// The constraint `lhs <= rhs`.
+    pub fn from_sums(lhs: LinearSum, rhs: LinearSum) -> LinearLeq {
+        let diff = lhs - rhs;
+        LinearLeq {
+            terms: diff.terms(),
+            upper_bound: -diff.constant,
+        }
+    }
+
+    pub fn to_expr(&self) -> Expr {
+        let mut args = Vec::with_capacity(1 + 2 * self.terms.len());
+        args.push(Atom::from(IAtom::from(self.upper_bound)));
+        for t in &self.terms {
+            args.push(Atom::from(IAtom::from(t.factor)));
+            args.push(Atom::from(IAtom::from(t.var)));
+        }
+        Expr::new(Fun::Linear, args)
+    }
+}
+
+impl TryFrom<&Expr> for LinearLeq {
+    type Error = ConversionError;
+
+    fn try_from(expr: &Expr) -> Result<Self, Self::Error> {
+        if expr.fun != Fun::Linear || expr.args.len() % 2 != 1 {
+            return Err(ConversionError::TypeError);
+        }
+        let constant = |atom: Atom| -> Result<IntCst, ConversionError> {
+            let atom = IAtom::try_from(atom)?;
+            match atom.var {
+                None => Ok(atom.shift),
+                Some(_) => Err(ConversionError::NotConstant),
+            }
+        };
+        let upper_bound = constant(expr.args[0])?;
+        let mut terms = Vec::with_capacity(expr.args.len() / 2);
+        for pair in expr.args[1..].chunks(2) {
+            let factor = constant(pair[0])?;
+            let var = IVar::try_from(IAtom::try_from(pair[1])?)?;
+            terms.push(LinearTerm { factor, var });
+        }
+        Ok(LinearLeq { terms, upper_bound })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Model;
+
+    #[test]
+    fn test_linear_sum() {
+        let mut model = Model::new();
+        let x = model.new_ivar(0, 10, "x");
+        let y = model.new_ivar(0, 10, "y");
+
+        // 2(x + 1) + 3y - x - 5  =  x + 3y - 3
+        let sum = LinearSum::from(x + 1) * 2 + LinearSum::from(y) * 3 - x - 5;
+        assert_eq!(
+            sum.terms(),
+            vec![LinearTerm { factor: 1, var: x }, LinearTerm { factor: 3, var: y }]
+        );
+        assert_eq!(sum.get_constant(), -3);
+
+        // x + 3y - 3 <= y   <=>  x + 2y <= 3
+        let leq = LinearLeq::from_sums(sum, y.into());
+        assert_eq!(leq.upper_bound, 3);
+        assert_eq!(leq.terms[1], LinearTerm { factor: 2, var: y });
+        assert_eq!(LinearLeq::try_from(&leq.to_expr()).unwrap(), leq);
+
+        // terms with a null factor are dropped
+        let sum = LinearSum::from(x) - x + y;
+        assert_eq!(sum.terms(), vec![LinearTerm { factor: 1, var: y }]);
+    }
+}
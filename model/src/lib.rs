@@ -7,6 +7,7 @@ pub mod lang;
 mod model;
 pub mod symbols;
 pub mod types;
+pub mod verification;
 
 // reexport the Label type
 pub use label::Label;
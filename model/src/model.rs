@@ -200,6 +200,52 @@ impl Model {
         self.intern_bool(and).into()
     }
 
+    /// Returns a boolean atom that is true iff exactly one of `a` and `b` is true.
+    pub fn xor2(&mut self, a: impl Into<BAtom>, b: impl Into<BAtom>) -> BAtom {
+        let a = a.into();
+        let b = b.into();
+        match (a, b) {
+            (BAtom::Cst(x), other) | (other, BAtom::Cst(x)) => {
+                return if x { !other } else { other };
+            }
+            _ if a == b => return BAtom::Cst(false),
+            _ if a == !b => return BAtom::Cst(true),
+            _ => {}
+        }
+        // normalize: each argument is the smallest of itself and its negation, and negations are
+        // moved outside of the expression  (!a xor b  <=>  !(a xor b))
+        let mut negated = false;
+        let mut normalize = |x: BAtom| {
+            if (!x).lexical_cmp(&x) == Ordering::Less {
+                negated = !negated;
+                !x
+            } else {
+                x
+            }
+        };
+        let mut a = normalize(a);
+        let mut b = normalize(b);
+        if b.lexical_cmp(&a) == Ordering::Less {
+            std::mem::swap(&mut a, &mut b);
+        }
+        let xor = self.intern_bool(Expr::new2(Fun::Xor, a, b));
+        if negated {
+            (!xor).into()
+        } else {
+            xor.into()
+        }
+    }
+
+    /// Returns a boolean atom that is true iff an odd number of the given atoms are true.
+    pub fn xor(&mut self, atoms: &[BAtom]) -> BAtom {
+        atoms.iter().fold(BAtom::Cst(false), |acc, &x| self.xor2(acc, x))
+    }
+
+    /// Returns a boolean atom that is true iff `a` and `b` have the same value.
+    pub fn bool_eq(&mut self, a: impl Into<BAtom>, b: impl Into<BAtom>) -> BAtom {
+        !self.xor2(a, b)
+    }
+
     pub fn leq<A: Into<IAtom>, B: Into<IAtom>>(&mut self, a: A, b: B) -> BAtom {
         let mut a = a.into();
         let mut b = b.into();
@@ -279,7 +325,7 @@ impl Model {
         } else {
             use Atom::*;
             match (a, b) {
-                (Bool(_a), Bool(_b)) => todo!(),
+                (Bool(a), Bool(b)) => self.bool_eq(a, b),
                 (Int(a), Int(b)) => self.int_eq(a, b),
                 (Sym(a), Sym(b)) => self.sym_eq(a, b),
                 _ => unreachable!(), // guarded by kind comparison
@@ -319,6 +365,153 @@ impl Model {
         !self.eq(a, b)
     }
 
+    /// Returns a boolean atom that is true iff all atoms take distinct values.
+    ///
+    /// Constants are excluded from the domains of the other atoms, and the remaining atoms
+    /// are represented by an expression of the [Fun::AllDifferent] function.
+    pub fn all_different(&mut self, atoms: &[IAtom]) -> BAtom {
+        let mut atoms = atoms.to_vec();
+        atoms.sort_by(IAtom::lexical_cmp);
+        if atoms.windows(2).any(|w| w[0] == w[1]) {
+            return BAtom::Cst(false);
+        }
+        let (vars, csts): (Vec<IAtom>, Vec<IAtom>) = atoms.into_iter().partition(|a| a.var.is_some());
+        let mut conjuncts = Vec::with_capacity(vars.len() * csts.len() + 1);
+        for &v in &vars {
+            for &c in &csts {
+                // v != c   <=>   v < c  ||  v > c
+                let lt = self.lt(v, c);
+                let gt = self.gt(v, c);
+                conjuncts.push(self.or2(lt, gt));
+            }
+        }
+        if vars.len() >= 2 {
+            let args = vars.iter().map(|&a| Atom::from(a)).collect();
+            conjuncts.push(self.intern_bool(Expr::new(Fun::AllDifferent, args)).into());
+        }
+        self.and(&conjuncts)
+    }
+
+    pub fn sym_all_different(&mut self, atoms: &[SAtom]) -> BAtom {
+        let atoms: Vec<IAtom> = atoms.iter().map(|a| a.int_view()).collect();
+        self.all_different(&atoms)
+    }
+
+    /// Returns a boolean atom that is true iff the values of `atoms` form one of the `lines` of a table.
+    ///
+    /// Lines that are incompatible with the constant atoms are dropped and the remaining ones are stored, restricted to
+    /// the variables, in an expression of the [Fun::Table] function.
+    pub fn in_table<'a>(&mut self, atoms: &[IAtom], lines: impl IntoIterator<Item = &'a [IntCst]>) -> BAtom {
+        let mut vars: Vec<IVar> = atoms.iter().filter_map(|a| a.var).collect();
+        vars.sort();
+        vars.dedup();
+        let mut tuples: Vec<Vec<IntCst>> = Vec::new();
+        'lines: for line in lines {
+            assert_eq!(line.len(), atoms.len(), "Line of the wrong size");
+            let mut tuple: Vec<Option<IntCst>> = vec![None; vars.len()];
+            for (atom, &value) in atoms.iter().zip(line) {
+                // value of the variable for the atom to take the value of the line
+                let value = value - atom.shift;
+                match atom.var {
+                    None if value != 0 => continue 'lines,
+                    None => {}
+                    Some(var) => {
+                        let col = vars.binary_search(&var).unwrap();
+                        match tuple[col] {
+                            Some(previous) if previous != value => continue 'lines,
+                            _ => tuple[col] = Some(value),
+                        }
+                    }
+                }
+            }
+            tuples.push(tuple.into_iter().map(Option::unwrap).collect());
+        }
+        tuples.sort();
+        tuples.dedup();
+        if tuples.is_empty() || vars.is_empty() {
+            return BAtom::Cst(!tuples.is_empty());
+        }
+        let mut args = Vec::with_capacity(1 + vars.len() * (tuples.len() + 1));
+        args.push(Atom::from(IAtom::from(vars.len() as IntCst)));
+        args.extend(vars.iter().map(|&v| Atom::from(IAtom::from(v))));
+        for tuple in tuples {
+            args.extend(tuple.into_iter().map(|v| Atom::from(IAtom::from(v))));
+        }
+        self.intern_bool(Expr::new(Fun::Table, args)).into()
+    }
+
+    /// Returns a boolean atom that is true iff `array[index] = value`, where the first element of the array
+    /// has index 0.
+    pub fn element(&mut self, index: impl Into<IAtom>, array: &[IAtom], value: impl Into<IAtom>) -> BAtom {
+        let index = index.into();
+        let value = value.into();
+        if index.var.is_none() {
+            return match array.get(index.shift as usize) {
+                Some(&elem) if index.shift >= 0 => self.int_eq(elem, value),
+                _ => BAtom::Cst(false),
+            };
+        }
+        let mut args = Vec::with_capacity(array.len() + 2);
+        args.push(Atom::from(index));
+        args.push(Atom::from(value));
+        args.extend(array.iter().map(|&a| Atom::from(a)));
+        self.intern_bool(Expr::new(Fun::Element, args)).into()
+    }
+
+    /// Returns a boolean atom that is true iff `lhs` is equal to the maximum of the variables in `rhs`.
+    pub fn eq_max(&mut self, lhs: IVar, rhs: &[IVar]) -> BAtom {
+        assert!(!rhs.is_empty(), "Max of an empty set");
+        let mut rhs = rhs.to_vec();
+        rhs.sort();
+        rhs.dedup();
+        if rhs.len() == 1 {
+            return self.int_eq(lhs, rhs[0]);
+        }
+        let mut args = Vec::with_capacity(rhs.len() + 1);
+        args.push(Atom::from(IAtom::from(lhs)));
+        args.extend(rhs.iter().map(|&v| Atom::from(IAtom::from(v))));
+        self.intern_bool(Expr::new(Fun::Max, args)).into()
+    }
+
+    /// Returns a boolean atom that is true iff `lhs <= rhs`.
+    ///
+    /// Constraints on a single variable are encoded as bounds. Other ones are represented by an expression of the
+    /// [Fun::Linear] function, including difference constraints (to be handled by a difference logic theory,
+    /// those must be created with [Model::leq]).
+    pub fn linear_leq(&mut self, lhs: impl Into<LinearSum>, rhs: impl Into<LinearSum>) -> BAtom {
+        let leq = LinearLeq::from_sums(lhs.into(), rhs.into());
+        let ub = leq.upper_bound;
+        match leq.terms.as_slice() {
+            [] => BAtom::Cst(0 <= ub),
+            &[LinearTerm { factor, var }] if factor > 0 => {
+                // factor * var <= ub   <=>  var <= floor(ub / factor)
+                Bound::leq(var, ub.div_euclid(factor)).into()
+            }
+            &[LinearTerm { factor, var }] => {
+                // - |factor| * var <= ub   <=>  var >= ceil(-ub / |factor|)
+                Bound::geq(var, -ub.div_euclid(-factor)).into()
+            }
+            _ => self.intern_bool(leq.to_expr()).into(),
+        }
+    }
+
+    pub fn linear_geq(&mut self, lhs: impl Into<LinearSum>, rhs: impl Into<LinearSum>) -> BAtom {
+        self.linear_leq(rhs, lhs)
+    }
+
+    pub fn linear_lt(&mut self, lhs: impl Into<LinearSum>, rhs: impl Into<LinearSum>) -> BAtom {
+        self.linear_leq(lhs.into() + 1, rhs)
+    }
+
+    /// Returns a boolean atom that is true iff `lhs = rhs`, as the conjunction of two linear inequalities.
+    pub fn linear_eq(&mut self, lhs: impl Into<LinearSum>, rhs: impl Into<LinearSum>) -> BAtom {
+        let lhs = lhs.into();
+        let rhs = rhs.into();
+        let leq = self.linear_leq(lhs.clone(), rhs.clone());
+        let geq = self.linear_leq(rhs, lhs);
+        self.and2(leq, geq)
+    }
+
     pub fn implies<A: Into<BAtom>, B: Into<BAtom>>(&mut self, a: A, b: B) -> BAtom {
         let a = a.into();
         let b = b.into();
@@ -0,0 +1,175 @@
+//! Independent checking of solutions: the constraints of a model are evaluated on a complete assignment,
+//! without relying on any of the reasoning performed by the solver.
+
+use crate::assignments::{Assignment, SavedAssignment};
+use crate::lang::{Atom, BAtom, Expr, Fun, IAtom, IntCst, LinearLeq};
+use crate::Model;
+use anyhow::*;
+use std::convert::TryFrom;
+
+/// Checks that the `solution` satisfies all `constraints`, that were built in the given `model`.
+/// Returns an error describing the first constraint that is violated or that could not be evaluated
+/// (e.g. because one of its variables has no value in the solution).
+pub fn verify_solution(model: &Model, constraints: &[BAtom], solution: &SavedAssignment) -> Result<()> {
+    for &constraint in constraints {
+        let satisfied =
+            evaluate(model, constraint, solution).with_context(|| format!("Constraint: {}", model.fmt(constraint)))?;
+        ensure!(satisfied, "Constraint violated: {}", model.fmt(constraint));
+    }
+    Ok(())
+}
+
+/// Evaluates a boolean atom in the given solution.
+pub fn evaluate(model: &Model, atom: BAtom, solution: &SavedAssignment) -> Result<bool> {
+    match atom {
+        BAtom::Cst(value) => Ok(value),
+        BAtom::Bound(b) => match solution.value_of_literal(b) {
+            Some(value) => Ok(value),
+            None => bail!("No value for {}", model.fmt(atom)),
+        },
+        BAtom::Expr(e) => {
+            let value = evaluate_expr(model, model.expressions.get(e.expr), solution)?;
+            Ok(value != e.negated)
+        }
+    }
+}
+
+/// Evaluates an integer atom in the given solution.
+pub fn evaluate_int(model: &Model, atom: IAtom, solution: &SavedAssignment) -> Result<IntCst> {
+    match solution.domain_of(atom) {
+        (lb, ub) if lb == ub => Ok(lb),
+        (lb, ub) => bail!("No value for {} (domain: [{}, {}])", model.fmt(atom), lb, ub),
+    }
+}
+
+/// Evaluates any atom as an integer. Booleans are interpreted as 0 (false) or 1 (true).
+fn evaluate_any(model: &Model, atom: Atom, solution: &SavedAssignment) -> Result<IntCst> {
+    match atom {
+        Atom::Bool(b) => Ok(evaluate(model, b, solution)? as IntCst),
+        Atom::Int(i) => evaluate_int(model, i, solution),
+        Atom::Sym(s) => evaluate_int(model, s.int_view(), solution),
+    }
+}
+
+fn evaluate_expr(model: &Model, expr: &Expr, solution: &SavedAssignment) -> Result<bool> {
+    let int_arg = |i: usize| -> Result<IntCst> {
+        let atom = IAtom::try_from(expr.args[i])?;
+        evaluate_int(model, atom, solution)
+    };
+    match expr.fun {
+        Fun::Or => {
+            for &arg in &expr.args {
+                if evaluate(model, BAtom::try_from(arg)?, solution)? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+        Fun::Eq => {
+            ensure!(expr.args.len() == 2, "Eq expects two arguments");
+            Ok(evaluate_any(model, expr.args[0], solution)? == evaluate_any(model, expr.args[1], solution)?)
+        }
+        Fun::Leq => {
+            ensure!(expr.args.len() == 2, "Leq expects two arguments");
+            Ok(int_arg(0)? <= int_arg(1)?)
+        }
+        Fun::Max => {
+            // the first argument is the maximum of all others
+            ensure!(expr.args.len() >= 2, "Max expects at least two arguments");
+            let mut max = IntCst::MIN;
+            for i in 1..expr.args.len() {
+                max = max.max(int_arg(i)?);
+            }
+            Ok(int_arg(0)? == max)
+        }
+        Fun::Linear => {
+            let leq = LinearLeq::try_from(expr)?;
+            let mut sum: i64 = 0;
+            for t in &leq.terms {
+                sum += t.factor as i64 * evaluate_int(model, t.var.into(), solution)? as i64;
+            }
+            Ok(sum <= leq.upper_bound as i64)
+        }
+        Fun::Xor => {
+            let mut parity = false;
+            for &arg in &expr.args {
+                parity ^= evaluate(model, BAtom::try_from(arg)?, solution)?;
+            }
+            Ok(parity)
+        }
+        Fun::AllDifferent => {
+            let mut values = Vec::with_capacity(expr.args.len());
+            for i in 0..expr.args.len() {
+                values.push(int_arg(i)?);
+            }
+            values.sort_unstable();
+            Ok(values.windows(2).all(|w| w[0] != w[1]))
+        }
+        Fun::Table => {
+            let n = int_arg(0)? as usize;
+            ensure!(n > 0 && expr.args.len() > n, "Malformed table");
+            let lines = expr.args[n + 1..].chunks_exact(n);
+            ensure!(lines.remainder().is_empty(), "Malformed table");
+            let mut values = Vec::with_capacity(n);
+            for i in 1..=n {
+                values.push(int_arg(i)?);
+            }
+            for line in lines {
+                let line: Vec<IntCst> = line
+                    .iter()
+                    .map(|&a| IAtom::try_from(a).map(|a| a.shift))
+                    .collect::<Result<_, _>>()?;
+                if line == values {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+        Fun::Element => {
+            ensure!(expr.args.len() >= 3, "Element expects at least three arguments");
+            let index = int_arg(0)? as i64;
+            let len = (expr.args.len() - 2) as i64;
+            Ok(0 <= index && index < len && int_arg(2 + index as usize)? == int_arg(1)?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounds::Bound;
+    use aries_backtrack::Backtrack;
+
+    #[test]
+    fn test_verification() {
+        let mut model = Model::new();
+        let a = model.new_bvar("a");
+        let x = model.new_ivar(0, 10, "x");
+        let y = model.new_ivar(0, 10, "y");
+        let x_leq_y = model.leq(x, y);
+        let x_eq_y = model.eq(x, y);
+        let a_or_x_eq_y = model.or(&[a.into(), x_eq_y]);
+        let constraints = vec![x_leq_y, a_or_x_eq_y];
+
+        let solution = |a_value: bool, x_value: IntCst, y_value: IntCst| {
+            let mut solution = model.clone();
+            solution.save_state();
+            let a_lit = if a_value { a.true_lit() } else { a.false_lit() };
+            solution.discrete.decide(a_lit).unwrap();
+            solution.discrete.decide(Bound::leq(x, x_value)).unwrap();
+            solution.discrete.decide(Bound::geq(x, x_value)).unwrap();
+            solution.discrete.decide(Bound::leq(y, y_value)).unwrap();
+            solution.discrete.decide(Bound::geq(y, y_value)).unwrap();
+            solution
+        };
+
+        assert!(verify_solution(&model, &constraints, &solution(true, 2, 3)).is_ok());
+        assert!(verify_solution(&model, &constraints, &solution(false, 3, 3)).is_ok());
+        let error = verify_solution(&model, &constraints, &solution(false, 2, 3)).unwrap_err();
+        assert!(error.to_string().contains("(or a > 0 (= x y))"), "{}", error);
+        assert!(verify_solution(&model, &constraints, &solution(true, 4, 3)).is_err());
+
+        // an incomplete assignment cannot be verified
+        assert!(verify_solution(&model, &constraints, &model).is_err());
+    }
+}
@@ -1,14 +1,20 @@
 #![allow(clippy::map_entry)]
 
 use anyhow::*;
+use aries_model::assignments::Assignment;
 use aries_model::bounds::Bound;
-use aries_model::lang::BAtom;
+use aries_model::lang::{BAtom, VarRef};
 use aries_model::Model;
-use aries_solver::solver::Solver;
+use aries_solver::solver::limits::SearchLimits;
+use aries_solver::solver::preprocessing::PreprocessingParams;
+use aries_solver::solver::proof::DratWriter;
+use aries_solver::solver::stats::write_stats_file;
+use aries_solver::solver::{SolveResult, Solver};
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -23,6 +29,18 @@ struct Opt {
     polarity: Option<bool>,
     #[structopt(long = "sat")]
     expected_satisfiability: Option<bool>,
+    /// Maximum time (in seconds) allowed for the search. If reached, the solver reports UNKNOWN.
+    #[structopt(long)]
+    timeout: Option<u64>,
+    /// Simplifies the problem (subsumption, variable elimination and failed literal probing) before search.
+    #[structopt(long)]
+    preprocess: bool,
+    /// File in which to write a DRAT proof of unsatisfiability, that can be checked with an external checker (e.g. drat-trim).
+    #[structopt(long)]
+    proof: Option<PathBuf>,
+    /// File in which to write the statistics of the solver, in CSV if it has a `.csv` extension and in JSON otherwise.
+    #[structopt(long)]
+    stats: Option<PathBuf>,
 }
 
 enum Source {
@@ -86,10 +104,26 @@ fn main() -> Result<()> {
     let input = source.read(&opt.file)?;
 
     let cnf = varisat_dimacs::DimacsParser::parse(input.as_bytes())?;
-    let (model, constraints) = load(cnf)?;
+    let Cnf {
+        model,
+        constraints,
+        clauses,
+        dimacs_indices,
+    } = load(cnf)?;
 
     let mut solver = Solver::new(model);
+    if let Some(proof_file) = &opt.proof {
+        let mut proof = DratWriter::to_file(proof_file)
+            .with_context(|| format!("Could not create proof file {}", proof_file.display()))?;
+        for (&var, &index) in &dimacs_indices {
+            proof.set_name(var, index);
+        }
+        solver.set_proof(proof);
+    }
     solver.enforce_all(&constraints);
+    if opt.preprocess {
+        solver.preprocess(&PreprocessingParams::default(), &[]);
+    }
     // solver.solve();
     // solver.model.discrete.print();
     //
@@ -99,31 +133,68 @@ fn main() -> Result<()> {
     //     Some(false) => solver.variables().for_each(|v| solver.set_polarity(v, false)),
     //     None => (),
     // };
-    if solver.solve() {
-        println!("SAT");
-        if opt.expected_satisfiability == Some(false) {
-            eprintln!("Error: expected UNSAT but got SAT");
-            std::process::exit(1);
+    if let Some(timeout) = opt.timeout {
+        solver.set_limits(SearchLimits::none().with_timeout(Duration::from_secs(timeout)));
+    }
+    let result = solver.solve();
+    if let Some(proof) = solver.take_proof() {
+        proof.finish().context("Could not write the proof")?;
+    }
+    match result {
+        SolveResult::Sat => {
+            println!("SAT");
+            let solution = solver.solution();
+            if let Some(clause) = clauses
+                .iter()
+                .find(|clause| !clause.iter().any(|&l| solution.entails(l)))
+            {
+                eprintln!("Error: solution violates clause {:?}", clause);
+                std::process::exit(1);
+            }
+            if opt.expected_satisfiability == Some(false) {
+                eprintln!("Error: expected UNSAT but got SAT");
+                std::process::exit(1);
+            }
         }
-    } else {
-        println!("UNSAT");
-        if opt.expected_satisfiability == Some(true) {
-            eprintln!("Error: expected SAT but got UNSAT");
-            std::process::exit(1);
+        SolveResult::Unsat => {
+            println!("UNSAT");
+            if opt.expected_satisfiability == Some(true) {
+                eprintln!("Error: expected SAT but got UNSAT");
+                std::process::exit(1);
+            }
         }
+        SolveResult::Unknown => {
+            println!("UNKNOWN");
+        }
+    }
+    solver.print_stats();
+    if let Some(file) = &opt.stats {
+        write_stats_file(&[solver.stats_record()], file).context("Could not write the statistics")?;
     }
-    println!("{}", solver.stats);
     Ok(())
 }
 
-pub fn load(cnf: varisat_formula::CnfFormula) -> Result<(Model, Vec<BAtom>)> {
+/// A CNF formula, encoded in a model.
+pub struct Cnf {
+    pub model: Model,
+    /// One constraint per clause, to be enforced in the solver.
+    pub constraints: Vec<BAtom>,
+    /// The clauses, expressed on the variables of the model (used to validate solutions).
+    pub clauses: Vec<Vec<Bound>>,
+    /// Index of each variable of the model in the DIMACS file.
+    pub dimacs_indices: HashMap<VarRef, u32>,
+}
+
+/// Builds a model from the CNF formula.
+pub fn load(cnf: varisat_formula::CnfFormula) -> Result<Cnf> {
     let mut var_bindings = HashMap::new();
+    let mut dimacs_indices = HashMap::new();
     let mut model = Model::new();
+    let mut constraints = Vec::new();
     let mut clauses = Vec::new();
 
-    let mut lits: Vec<BAtom> = Vec::new();
     for clause in cnf.iter() {
-        lits.clear();
+        let mut lits: Vec<Bound> = Vec::with_capacity(clause.len());
         for &lit in clause {
             let var = lit.var();
             let var = if let Some(var) = var_bindings.get(&var) {
@@ -131,13 +202,21 @@ pub fn load(cnf: varisat_formula::CnfFormula) -> Result<(Model, Vec<BAtom>)> {
             } else {
                 let model_var = model.new_bvar(var.to_dimacs().to_string());
                 var_bindings.insert(var, model_var);
+                dimacs_indices.insert(VarRef::from(model_var), var.to_dimacs() as u32);
                 model_var
             };
             let lit: Bound = if lit.is_positive() { var.into() } else { !var };
-            lits.push(lit.into());
+            lits.push(lit);
         }
-        clauses.push(model.or(&lits));
+        let atoms: Vec<BAtom> = lits.iter().map(|&l| l.into()).collect();
+        constraints.push(model.or(&atoms));
+        clauses.push(lits);
     }
 
-    Ok((model, clauses))
+    Ok(Cnf {
+        model,
+        constraints,
+        clauses,
+        dimacs_indices,
+    })
 }
@@ -2,26 +2,89 @@ use aries_backtrack::EventIndex;
 use aries_collections::ref_store::{RefMap, RefVec};
 use aries_collections::*;
 use aries_model::bounds::{Bound, Disjunction};
+use env_param::EnvParam;
 use std::cmp::Ordering::Equal;
 use std::fmt::{Debug, Display, Error, Formatter};
 use std::ops::{Index, IndexMut};
 
+/// Learnt clauses whose LBD is lower or equal to this value are placed in the core tier and never removed.
+pub static CORE_LBD: EnvParam<u32> = EnvParam::new("ARIES_SMT_CORE_LBD", "2");
+
+/// Learnt clauses whose LBD is lower or equal to this value (and not part of the core) are placed in tier 2,
+/// where they are kept as long as they are used between two reductions of the database.
+pub static TIER2_LBD: EnvParam<u32> = EnvParam::new("ARIES_SMT_TIER2_LBD", "6");
+
 pub struct ClausesParams {
     cla_inc: f64,
     cla_decay: f64,
+    /// Maximum LBD of a clause in the core tier.
+    core_lbd: u32,
+    /// Maximum LBD of a clause in the tier 2.
+    tier2_lbd: u32,
 }
 impl Default for ClausesParams {
     fn default() -> Self {
         ClausesParams {
             cla_inc: 1_f64,
             cla_decay: 0.999_f64,
+            core_lbd: *CORE_LBD.get(),
+            tier2_lbd: *TIER2_LBD.get(),
         }
     }
 }
 
+/// Retention tier of a learnt clause, determined by its Literal Block Distance (LBD).
+/// Tiers are ordered from the most to the least valuable.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum ClauseTier {
+    /// Clauses with a very low LBD, that are never removed from the database.
+    Core,
+    /// Clauses with a low LBD, that are kept as long as they are used.
+    /// Those unused since the last reduction are demoted to the local tier.
+    Tier2,
+    /// All other learnt clauses. On each reduction, the least active half of them are removed.
+    Local,
+}
+
 struct ClauseMetadata {
     pub activity: f64,
     pub learnt: bool,
+    /// Literal Block Distance of the clause, the lowest one observed since the clause was learnt.
+    pub lbd: u32,
+    /// Retention tier of the clause. Only meaningful for learnt clauses.
+    pub tier: ClauseTier,
+    /// True if the clause was involved in a conflict or an explanation since the last reduction of the database.
+    pub used: bool,
+}
+
+/// Size of the clause database, with learnt clauses divided by tier.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct DbSize {
+    /// Number of clauses of the problem, that cannot be removed.
+    pub fixed: usize,
+    pub core: usize,
+    pub tier2: usize,
+    pub local: usize,
+}
+
+impl DbSize {
+    pub fn num_learnt(&self) -> usize {
+        self.core + self.tier2 + self.local
+    }
+}
+
+impl Display for DbSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "{} fixed, {} learnt (core: {}, tier2: {}, local: {})",
+            self.fixed,
+            self.num_learnt(),
+            self.core,
+            self.tier2,
+            self.local
+        )
+    }
 }
 
 /// A clause represents a disjunction of literals together with some metadata needed to decide whether
@@ -224,6 +287,8 @@ pub struct ClauseDB {
     num_fixed: usize,
     /// Total number of clauses.
     num_clauses: usize,
+    /// Number of learnt clauses in the core tier, that are never removed from the database.
+    num_core: usize,
     first_possibly_free: usize,
     /// Associates each clause id to to a clause.
     /// Unassigned clause ids point to a tautological clause in order to always point to valid one.
@@ -246,6 +311,7 @@ impl ClauseDB {
             params,
             num_fixed: 0,
             num_clauses: 0,
+            num_core: 0,
             first_possibly_free: 0,
             clauses: RefVec::new(),
             metadata: RefMap::default(),
@@ -260,13 +326,52 @@ impl ClauseDB {
             && clause.unwatched.is_empty()
     }
 
+    fn tier(&self, lbd: u32) -> ClauseTier {
+        if lbd <= self.params.core_lbd {
+            ClauseTier::Core
+        } else if lbd <= self.params.tier2_lbd {
+            ClauseTier::Tier2
+        } else {
+            ClauseTier::Local
+        }
+    }
+
+    /// Adds a clause to the database.
+    /// A learnt clause added through this method is assumed to have an LBD equal to its length.
     pub fn add_clause(&mut self, cl: Clause, learnt: bool) -> ClauseId {
-        self.num_clauses += 1;
-        if !learnt {
+        if learnt {
+            let lbd = cl.len() as u32;
+            self.add_learnt_clause(cl, lbd)
+        } else {
             self.num_fixed += 1;
+            let meta = ClauseMetadata {
+                activity: 0f64,
+                learnt: false,
+                lbd: cl.len() as u32,
+                tier: ClauseTier::Core,
+                used: false,
+            };
+            self.insert(cl, meta)
+        }
+    }
+
+    /// Adds a learnt clause with the given Literal Block Distance (LBD), that determines its retention tier.
+    pub fn add_learnt_clause(&mut self, cl: Clause, lbd: u32) -> ClauseId {
+        let meta = ClauseMetadata {
+            activity: 0f64,
+            learnt: true,
+            lbd,
+            tier: self.tier(lbd),
+            used: false,
+        };
+        if meta.tier == ClauseTier::Core {
+            self.num_core += 1;
         }
+        self.insert(cl, meta)
+    }
 
-        let meta = ClauseMetadata { activity: 0f64, learnt };
+    fn insert(&mut self, cl: Clause, meta: ClauseMetadata) -> ClauseId {
+        self.num_clauses += 1;
 
         debug_assert!((0..self.first_possibly_free).all(|i| self.is_in_db(ClauseId::from(i))));
 
@@ -306,12 +411,61 @@ impl ClauseDB {
     pub fn num_learnt(&self) -> usize {
         self.num_clauses - self.num_fixed
     }
+    /// Number of learnt clauses that may be removed by a reduction of the database (i.e. not in the core tier).
+    pub fn num_removable(&self) -> usize {
+        self.num_learnt() - self.num_core
+    }
 
     pub fn all_clauses(&self) -> impl Iterator<Item = ClauseId> + '_ {
         self.metadata.keys()
     }
 
+    /// Returns the current size of the database.
+    pub fn size(&self) -> DbSize {
+        let mut size = DbSize::default();
+        for meta in self.metadata.values() {
+            if !meta.learnt {
+                size.fixed += 1;
+                continue;
+            }
+            match meta.tier {
+                ClauseTier::Core => size.core += 1,
+                ClauseTier::Tier2 => size.tier2 += 1,
+                ClauseTier::Local => size.local += 1,
+            }
+        }
+        size
+    }
+
+    /// Returns the retention tier of the clause, or `None` if it is not a learnt clause.
+    pub fn learnt_tier(&self, cl: ClauseId) -> Option<ClauseTier> {
+        let meta = &self.metadata[cl];
+        if meta.learnt {
+            Some(meta.tier)
+        } else {
+            None
+        }
+    }
+
+    /// Records a newly observed LBD for the clause. If it is lower than the previously known one,
+    /// the clause may be promoted to a better tier.
+    pub fn update_lbd(&mut self, cl: ClauseId, lbd: u32) {
+        let tier = self.tier(lbd);
+        let meta = &mut self.metadata[cl];
+        if meta.learnt && lbd < meta.lbd {
+            meta.lbd = lbd;
+            if tier < meta.tier {
+                if tier == ClauseTier::Core {
+                    self.num_core += 1;
+                }
+                meta.tier = tier;
+            }
+        }
+    }
+
+    /// Increases the activity of the clause and marks it as used since the last reduction.
     pub fn bump_activity(&mut self, cl: ClauseId) {
+        self.metadata[cl].used = true;
         self.metadata[cl].activity += self.params.cla_inc;
         if self.metadata[cl].activity > 1e100_f64 {
             self.rescale_activities()
@@ -329,12 +483,32 @@ impl ClauseDB {
         self.params.cla_inc *= 1e-100_f64;
     }
 
-    pub fn reduce_db<F: Fn(ClauseId) -> bool>(&mut self, locked: F, remove_watch: &mut impl FnMut(ClauseId, Bound)) {
+    /// Removes learnt clauses from the database, following a three-tier retention policy:
+    ///  - clauses of the core tier are always kept,
+    ///  - clauses of the tier 2 are kept if they were used since the last reduction and demoted to the local tier otherwise,
+    ///  - the least active half of the (unlocked) clauses of the local tier are removed.
+    ///
+    /// Returns the clauses removed.
+    pub fn reduce_db<F: Fn(ClauseId) -> bool>(
+        &mut self,
+        locked: F,
+        remove_watch: &mut impl FnMut(ClauseId, Bound),
+    ) -> Vec<Clause> {
+        let unused_tier2: Vec<ClauseId> = self
+            .metadata
+            .entries()
+            .filter(|(_, meta)| meta.learnt && meta.tier == ClauseTier::Tier2 && !meta.used)
+            .map(|(id, _)| id)
+            .collect();
+        for id in unused_tier2 {
+            self.metadata[id].tier = ClauseTier::Local;
+        }
+
         let mut clauses: Vec<_> = self
             .metadata
             .entries()
             .filter_map(|(id, meta)| {
-                if meta.learnt && !locked(id) {
+                if meta.learnt && meta.tier == ClauseTier::Local && !locked(id) {
                     Some((id, meta.activity))
                 } else {
                     None
@@ -344,21 +518,45 @@ impl ClauseDB {
 
         clauses.sort_by(|&a, &b| a.1.partial_cmp(&b.1).unwrap_or(Equal));
         // remove half removable
-        clauses.iter().take(clauses.len() / 2).for_each(|&(id, _)| {
-            let cl = &self.clauses[id];
-            if !cl.is_empty() {
-                remove_watch(id, !cl.watch1);
-            }
-            if cl.len() >= 2 {
-                remove_watch(id, !cl.watch2);
-            }
-            self.clauses[id] = self.tautological_clause.clone();
-            self.metadata.remove(id);
-            self.num_clauses -= 1;
-        });
+        let num_removed = clauses.len() / 2;
+        let removed = clauses
+            .iter()
+            .take(num_removed)
+            .map(|&(id, _)| self.remove_clause(id, remove_watch))
+            .collect();
+
+        for meta in self.metadata.values_mut() {
+            meta.used = false;
+        }
+        removed
+    }
 
+    /// Removes a clause from the database, be it learnt or not, and returns it.
+    pub fn remove_clause(&mut self, id: ClauseId, remove_watch: &mut impl FnMut(ClauseId, Bound)) -> Clause {
+        let cl = &self.clauses[id];
+        if !cl.is_empty() {
+            remove_watch(id, !cl.watch1);
+        }
+        if cl.len() >= 2 {
+            remove_watch(id, !cl.watch2);
+        }
+        let meta = &self.metadata[id];
+        if !meta.learnt {
+            self.num_fixed -= 1;
+        } else if meta.tier == ClauseTier::Core {
+            self.num_core -= 1;
+        }
+        let removed = std::mem::replace(&mut self.clauses[id], self.tautological_clause.clone());
+        self.metadata.remove(id);
+        self.num_clauses -= 1;
         // make sure we search for free spots from the beginning
-        self.first_possibly_free = 0;
+        self.first_possibly_free = self.first_possibly_free.min(usize::from(id));
+        removed
+    }
+
+    /// Returns true if the clause was learnt, i.e., is not part of the problem definition.
+    pub fn is_learnt(&self, id: ClauseId) -> bool {
+        self.metadata[id].learnt
     }
 
     /// Returns true is the clause id is assigned to a clause
@@ -381,3 +579,72 @@ impl IndexMut<ClauseId> for ClauseDB {
         &mut self.clauses[k]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aries_model::Model;
+
+    #[test]
+    fn test_tiered_reduction() {
+        let model = &mut Model::new();
+        let vars: Vec<_> = (0..4).map(|i| model.new_bvar(format!("x{}", i))).collect();
+        let clause = || Clause::new(Disjunction::new(vars.iter().map(|v| v.true_lit()).collect()));
+
+        let mut db = ClauseDB::new(ClausesParams::default(), model.tautology);
+        let fixed = db.add_clause(clause(), false);
+        let core = db.add_learnt_clause(clause(), 2);
+        let tier2_used = db.add_learnt_clause(clause(), 5);
+        let tier2_unused = db.add_learnt_clause(clause(), 5);
+        let local: Vec<_> = (0..4).map(|_| db.add_learnt_clause(clause(), 10)).collect();
+        assert_eq!(db.learnt_tier(fixed), None);
+        assert_eq!(db.learnt_tier(core), Some(ClauseTier::Core));
+        assert_eq!(
+            db.size(),
+            DbSize {
+                fixed: 1,
+                core: 1,
+                tier2: 2,
+                local: 4
+            }
+        );
+        assert_eq!(db.num_removable(), 6);
+
+        db.bump_activity(tier2_used);
+        db.bump_activity(local[1]);
+        db.bump_activity(local[2]);
+        db.bump_activity(local[3]);
+
+        let removed = db.reduce_db(|_| false, &mut |_, _| {});
+        // the unused tier 2 clause is demoted to the local tier, where it is removed together with
+        // the least active local clause
+        assert_eq!(removed.len(), 2);
+        assert!(db.is_in_db(fixed));
+        assert!(db.is_in_db(core));
+        assert!(db.is_in_db(tier2_used));
+        assert!(!db.is_in_db(tier2_unused));
+        assert!(!db.is_in_db(local[0]));
+        assert!(local[1..].iter().all(|&cl| db.is_in_db(cl)));
+
+        // a lower LBD observed later promotes a local clause to the core
+        db.update_lbd(local[2], 1);
+        assert_eq!(db.learnt_tier(local[2]), Some(ClauseTier::Core));
+        assert_eq!(
+            db.size(),
+            DbSize {
+                fixed: 1,
+                core: 2,
+                tier2: 1,
+                local: 2
+            }
+        );
+
+        // the tier 2 clause was not used since the last reduction: it is demoted and removed as the least active
+        db.bump_activity(local[1]);
+        db.bump_activity(local[3]);
+        let removed = db.reduce_db(|_| false, &mut |_, _| {});
+        assert_eq!(removed.len(), 1);
+        assert!(!db.is_in_db(tier2_used));
+        assert!(local[1..].iter().all(|&cl| db.is_in_db(cl)));
+    }
+}
@@ -3,6 +3,7 @@ pub(crate) mod cpu_time;
 pub mod solver;
 pub mod theories;
 
+use crate::solver::stats::StatValue;
 use crate::solver::{Binding, BindingResult};
 use aries_backtrack::Backtrack;
 use aries_backtrack::ObsTrail;
@@ -23,7 +24,17 @@ pub trait Theory: Backtrack {
     // TODO: use inner type instead of u64
     fn explain(&mut self, literal: Bound, context: u32, model: &DiscreteModel, out_explanation: &mut Explanation);
 
-    fn print_stats(&self);
+    /// Statistics specific to this theory, as (key, value) pairs.
+    /// They appear in the [StatsRecord](crate::solver::stats::StatsRecord) of the solver with a `theory<i>.` prefix.
+    fn stats(&self) -> Vec<(String, StatValue)> {
+        Vec::new()
+    }
+
+    fn print_stats(&self) {
+        for (key, value) in self.stats() {
+            println!("# {}: {}", key, value);
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -1,4 +1,11 @@
 pub mod brancher;
+pub mod limits;
+pub mod monitor;
+pub mod optimization;
+pub mod parallel;
+pub mod preprocessing;
+pub mod proof;
+pub mod restarts;
 pub mod sat_solver;
 pub mod stats;
 pub mod theory_solver;
@@ -6,12 +13,18 @@ pub mod theory_solver;
 use crate::{Contradiction, Theory};
 use aries_backtrack::ObsTrail;
 use aries_backtrack::{Backtrack, DecLvl};
-use aries_model::lang::{BAtom, BExpr, IAtom, IntCst};
-use aries_model::{Model, WriterId};
+use aries_model::lang::{BAtom, BExpr, IAtom, IntCst, VarRef};
+use aries_model::{Label, Model, WriterId};
 
-use crate::solver::brancher::{Brancher, Decision};
+use crate::solver::brancher::{ActivityBrancher, Brancher, Decision};
+use crate::solver::limits::SearchLimits;
+use crate::solver::monitor::SearchMonitor;
+use crate::solver::optimization::{ObjectiveBounds, OptimizationResult, OptimizationStrategy, OPTIMIZATION_STRATEGY};
+use crate::solver::parallel::ClauseSharing;
+use crate::solver::preprocessing::{PreprocessingParams, Preprocessor, Reconstruction, INPROCESSING_PERIOD};
+use crate::solver::proof::DratWriter;
 use crate::solver::sat_solver::SatSolver;
-use crate::solver::stats::Stats;
+use crate::solver::stats::{module_name, Stats, StatsRecord};
 use crate::solver::theory_solver::TheorySolver;
 use aries_model::assignments::{Assignment, SavedAssignment};
 use aries_model::int_model::{DiscreteModel, Explainer, Explanation, InferenceCause};
@@ -20,10 +33,27 @@ use crate::cpu_time::CycleCount;
 use crate::cpu_time::StartCycleCount;
 use aries_model::bounds::{Bound, Disjunction};
 use env_param::EnvParam;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Instant;
 
 pub static OPTIMIZE_USES_LNS: EnvParam<bool> = EnvParam::new("ARIES_SMT_OPTIMIZE_USES_LNS", "true");
 
+/// If true, learnt clauses are minimized by removing the literals that are implied by other literals of the clause.
+pub static MINIMIZE_LEARNT_CLAUSES: EnvParam<bool> = EnvParam::new("ARIES_SMT_MINIMIZE_LEARNT_CLAUSES", "true");
+
+/// If true, the clause database is simplified each time new literals are fixed at the root level.
+pub static SIMPLIFY_AT_ROOT: EnvParam<bool> = EnvParam::new("ARIES_SMT_SIMPLIFY_AT_ROOT", "true");
+
+/// Threshold for chronological backtracking (Nadel & Ryvchin, 2018): when a conflict would make the solver backjump
+/// over more than this number of decision levels, it only undoes the latest decision level of the learnt clause,
+/// keeping the rest of the trail (and the associated theory propagations) intact. Zero disables it.
+pub static CHRONO_BACKTRACK_THRESHOLD: EnvParam<u32> = EnvParam::new("ARIES_SMT_CHRONO_BACKTRACK_THRESHOLD", "0");
+
+/// Maximum number of conflicts allowed when extending a warm start into a complete solution.
+pub static WARM_START_MAX_CONFLICTS: EnvParam<u64> = EnvParam::new("ARIES_SMT_WARM_START_MAX_CONFLICTS", "1000");
+
 struct Reasoners {
     sat: SatSolver,
     theories: Vec<TheorySolver>,
@@ -61,21 +91,62 @@ impl Explainer for Reasoners {
 
 pub struct Solver {
     pub model: Model,
-    brancher: Brancher,
+    brancher: Box<dyn Brancher>,
     reasoners: Reasoners,
     decision_level: DecLvl,
+    /// Assumption that was found to be violated in the last call to `solve_with_assumptions`, if any.
+    failed_assumption: Option<Bound>,
+    /// Subset of the assumptions of the last call to `solve_with_assumptions` that are jointly unsatisfiable.
+    unsat_core: Vec<Bound>,
+    /// Activation literals of the constraints enforced through `enforce_tracked`, together with their labels.
+    tracked: Vec<(Bound, Label)>,
+    /// True if a conflict was found at the root decision level, meaning that the problem is unsatisfiable.
+    /// This must be remembered as the conflicting clause would not be detected again by subsequent propagations.
+    proven_unsat: bool,
+    /// Limits on the search effort, beyond which the search is stopped.
+    limits: SearchLimits,
+    /// Flag that can be raised (possibly from another thread) to stop the search.
+    interrupt: Arc<AtomicBool>,
+    /// Channel through which learnt clauses are exchanged with other solvers, when part of a portfolio.
+    sharing: Option<ClauseSharing>,
+    /// Clauses removed by the elimination of variables during preprocessing, needed to restore their values in solutions.
+    reconstruction: Reconstruction,
+    /// Observers notified of the events of the search.
+    monitors: Vec<Box<dyn SearchMonitor>>,
+    /// Backjumps over more than this number of decision levels are replaced by chronological backtracks
+    /// (see [CHRONO_BACKTRACK_THRESHOLD]). Zero disables chronological backtracking.
+    chrono_backtrack_threshold: u32,
     pub stats: Stats,
 }
 impl Solver {
-    pub fn new(mut model: Model) -> Solver {
+    pub fn new(model: Model) -> Solver {
+        Self::with_brancher(model, Box::new(ActivityBrancher::new()))
+    }
+
+    /// Creates a new solver whose decisions are made by the given brancher.
+    pub fn with_brancher(mut model: Model, brancher: Box<dyn Brancher>) -> Solver {
         let sat_id = model.new_write_token();
         let sat = SatSolver::new(sat_id, &mut model);
+        let stats = Stats {
+            restart_policy: brancher.restart_policy(),
+            ..Default::default()
+        };
         Solver {
             model,
-            brancher: Brancher::new(),
+            brancher,
             reasoners: Reasoners::new(sat, sat_id),
             decision_level: DecLvl::ROOT,
-            stats: Default::default(),
+            failed_assumption: None,
+            unsat_core: Vec::new(),
+            tracked: Vec::new(),
+            proven_unsat: false,
+            limits: SearchLimits::none(),
+            interrupt: Arc::new(AtomicBool::new(false)),
+            sharing: None,
+            reconstruction: Reconstruction::default(),
+            monitors: Vec::new(),
+            chrono_backtrack_threshold: *CHRONO_BACKTRACK_THRESHOLD.get(),
+            stats,
         }
     }
     pub fn add_theory(&mut self, theory: Box<dyn Theory>) {
@@ -92,9 +163,13 @@ impl Solver {
     }
 
     /// Impose the constraint that all given boolean atoms are true in the final model.
+    ///
+    /// Constraints are always added at the root decision level: any decision made by a previous
+    /// call to `solve` is retracted.
     pub fn enforce_all(&mut self, constraints: &[BAtom]) {
         let start_time = Instant::now();
         let start_cycles = StartCycleCount::now();
+        self.reset();
         let mut queue = ObsTrail::new();
         let mut reader = queue.reader();
 
@@ -148,64 +223,594 @@ impl Solver {
         self.stats.init_cycles += start_cycles.elapsed();
     }
 
-    pub fn solve(&mut self) -> bool {
+    /// Impose the constraint that the given boolean atom is true in the final model, keeping track of it under
+    /// the given label.
+    ///
+    /// The constraint is only enforced when its activation literal (the returned one) holds. Activation literals
+    /// of all tracked constraints are assumed in every call to `solve`, so that, if the problem is unsatisfiable,
+    /// the responsible constraints can be identified with [Solver::conflicting_constraints].
+    pub fn enforce_tracked(&mut self, label: impl Into<Label>, constraint: impl Into<BAtom>) -> Bound {
+        let label = label.into();
+        let activation = self.model.new_bvar(label.clone());
+        let constraint = self.model.implies(activation, constraint.into());
+        self.enforce(constraint);
+        self.tracked.push((activation.true_lit(), label));
+        activation.true_lit()
+    }
+
+    /// Sets the limits on the search effort. When any of them is reached, the search is stopped
+    /// with an `Unknown` result.
+    pub fn set_limits(&mut self, limits: SearchLimits) {
+        self.limits = limits;
+    }
+
+    /// Sets the threshold for chronological backtracking, overriding the one given by [CHRONO_BACKTRACK_THRESHOLD].
+    /// Zero disables chronological backtracking.
+    pub fn set_chrono_backtrack_threshold(&mut self, threshold: u32) {
+        self.chrono_backtrack_threshold = threshold;
+    }
+
+    /// Records a DRAT proof of all clauses learnt and deleted by the solver. If the problem is proven unsatisfiable,
+    /// the proof ends with the empty clause and can be checked against the original problem by an external checker.
+    ///
+    /// Must be called before any constraint is enforced. Proofs are only meaningful for purely boolean problems
+    /// solved without assumptions, objective, enumeration or clause sharing, as the clauses they introduce
+    /// are not implied by the problem.
+    pub fn set_proof(&mut self, proof: DratWriter) {
+        self.reasoners.sat.set_proof(proof);
+    }
+
+    /// Stops recording the proof and returns it, to be finalized with [DratWriter::finish].
+    pub fn take_proof(&mut self) -> Option<DratWriter> {
+        self.reasoners.sat.take_proof()
+    }
+
+    /// Returns a flag that can be raised, possibly from another thread, to stop the search of this solver.
+    /// The solver does not lower the flag itself: all subsequent searches will be stopped until it is lowered.
+    pub fn interrupt_flag(&self) -> Arc<AtomicBool> {
+        self.interrupt.clone()
+    }
+
+    /// Registers a monitor that will be notified of all subsequent events of the search.
+    pub fn add_monitor(&mut self, monitor: Box<dyn SearchMonitor>) {
+        self.monitors.push(monitor);
+    }
+
+    /// Returns true if the search should be stopped, either because of an interruption, a search limit
+    /// or at the request of a monitor.
+    fn should_stop(&mut self) -> bool {
+        if self.interrupt.load(Ordering::Relaxed) || self.limits.exceeded(&self.stats) {
+            return true;
+        }
+        let stats = &self.stats;
+        self.monitors.iter_mut().any(|monitor| monitor.should_stop(stats))
+    }
+
+    pub fn solve(&mut self) -> SolveResult {
+        self.solve_with_assumptions(&[])
+    }
+
+    /// Searches for a solution in which all `assumptions` hold.
+    ///
+    /// Each assumption is decided on its own decision level, before any other decision is made.
+    /// Assumptions are only temporary: they are retracted at the beginning of the next call to `solve`
+    /// or `solve_with_assumptions`. Clauses learnt in the meantime are kept, as they do not depend on the assumptions.
+    ///
+    /// Returns `Sat` if a solution was found. When `Unsat` is returned because an assumption was refuted
+    /// (by the constraints and the previous assumptions), this assumption is available through
+    /// [Solver::failed_assumption] and the assumptions responsible for the failure through [Solver::unsat_core].
+    /// Otherwise, the problem is unsatisfiable regardless of the assumptions and the unsat core is empty.
+    /// `Unknown` is returned if the search was interrupted or reached one of its limits.
+    ///
+    /// The activation literals of tracked constraints are implicitly assumed before the given `assumptions`.
+    pub fn solve_with_assumptions(&mut self, assumptions: &[Bound]) -> SolveResult {
+        if self.tracked.is_empty() {
+            self.search(assumptions)
+        } else {
+            let all_assumptions: Vec<Bound> = self
+                .tracked
+                .iter()
+                .map(|(activation, _)| *activation)
+                .chain(assumptions.iter().copied())
+                .collect();
+            self.search(&all_assumptions)
+        }
+    }
+
+    /// Searches for a solution in which all `assumptions` hold, see [Solver::solve_with_assumptions].
+    fn search(&mut self, assumptions: &[Bound]) -> SolveResult {
         let start_time = Instant::now();
         let start_cycles = StartCycleCount::now();
+        self.failed_assumption = None;
+        self.unsat_core.clear();
+        // retract any decision (and assumption) from a previous call
+        self.reset();
+        self.import_shared_clauses();
         loop {
             if !self.propagate_and_backtrack_to_consistent() {
                 // UNSAT
                 self.stats.solve_time += start_time.elapsed();
                 self.stats.solve_cycles += start_cycles.elapsed();
-                return false;
+                return SolveResult::Unsat;
+            }
+            if self.should_stop() {
+                self.stats.solve_time += start_time.elapsed();
+                self.stats.solve_cycles += start_cycles.elapsed();
+                return SolveResult::Unknown;
+            }
+            if self.decision_level == DecLvl::ROOT && *SIMPLIFY_AT_ROOT.get() {
+                self.reasoners.sat.simplify(&self.model.discrete);
+            }
+            // the i-th assumption is always decided on the (i+1)-th decision level
+            let next_assumption = self.decision_level.to_int() as usize;
+            if next_assumption < assumptions.len() {
+                let lit = assumptions[next_assumption];
+                match self.model.discrete.value(lit) {
+                    Some(true) => {
+                        // already entailed, open an empty decision level to keep the invariant above
+                        self.save_state();
+                    }
+                    Some(false) => {
+                        // UNSAT under the given assumptions
+                        self.failed_assumption = Some(lit);
+                        self.unsat_core = self.extract_unsat_core(lit, assumptions);
+                        self.stats.solve_time += start_time.elapsed();
+                        self.stats.solve_cycles += start_cycles.elapsed();
+                        return SolveResult::Unsat;
+                    }
+                    None => {
+                        self.save_state();
+                        self.model.discrete.decide(lit).unwrap();
+                    }
+                }
+                continue;
             }
             match self.brancher.next_decision(&self.stats, &self.model) {
                 Some(Decision::SetLiteral(lit)) => {
                     // println!("Decision on: {} -- {:?}", self.model.discrete.fmt(lit.variable()), lit);
                     self.decide(lit);
+                    for monitor in &mut self.monitors {
+                        monitor.on_decision(lit, self.decision_level, &self.stats);
+                    }
                 }
                 Some(Decision::Restart) => {
                     self.reset();
+                    self.import_shared_clauses();
                     self.stats.num_restarts += 1;
+                    for monitor in &mut self.monitors {
+                        monitor.on_restart(&self.stats);
+                    }
+                    let period = *INPROCESSING_PERIOD.get();
+                    if period > 0 && self.stats.num_restarts.is_multiple_of(period) {
+                        self.preprocess(&PreprocessingParams::inprocessing(), &[]);
+                    }
                 }
                 None => {
                     // SAT: consistent + no choices left
                     self.stats.solve_time += start_time.elapsed();
                     self.stats.solve_cycles += start_cycles.elapsed();
-                    return true;
+                    for monitor in &mut self.monitors {
+                        monitor.on_solution(&self.model, &self.stats);
+                    }
+                    return SolveResult::Sat;
                 }
             }
         }
     }
 
+    /// Simplifies the clauses of the problem with the techniques enabled in `params` (see [preprocessing]).
+    /// If the problem is found to be unsatisfiable, all subsequent calls to `solve` will return `Unsat`.
+    ///
+    /// Variables are only eliminated on problems without any theory and are never eliminated if they are `frozen`
+    /// or are the activation literals of tracked constraints. Since eliminated variables disappear from the problem,
+    /// any variable that may later appear in a constraint, an assumption, an objective or an enumeration
+    /// must be frozen.
+    /// In the solutions provided by [Solver::solution], eliminated variables are given a value that satisfies
+    /// the original clauses.
+    pub fn preprocess(&mut self, params: &PreprocessingParams, frozen: &[VarRef]) {
+        self.reset();
+        if !self.propagate_and_backtrack_to_consistent() {
+            return;
+        }
+        let clauses = self.reasoners.sat.take_problem_clauses(&self.model.discrete);
+        let mut preprocessor = Preprocessor::new(params.clone());
+        if self.reasoners.sat.proof_mut().is_some() {
+            preprocessor.record_proof();
+        }
+        for clause in clauses {
+            preprocessor.add_clause(clause);
+        }
+        let frozen: HashSet<VarRef> = frozen
+            .iter()
+            .copied()
+            .chain(self.tracked.iter().map(|(activation, _)| activation.variable()))
+            .collect();
+        let no_theories = self.reasoners.theories.is_empty();
+        let model = &self.model;
+        preprocessor.run(|var| no_theories && !frozen.contains(&var) && model.discrete.domain_of(var) == (0, 1));
+
+        if let Some(proof) = self.reasoners.sat.proof_mut() {
+            for step in preprocessor.take_proof() {
+                proof.write_step(&step);
+            }
+        }
+        if preprocessor.is_unsat() {
+            self.set_unsat();
+        } else {
+            for clause in preprocessor.clauses() {
+                self.reasoners.sat.restore_problem_clause(clause);
+            }
+        }
+        let reconstruction = preprocessor.reconstruction();
+        if !reconstruction.is_empty() {
+            // learnt clauses might involve the eliminated variables
+            let eliminated: HashSet<VarRef> = reconstruction.eliminated_variables().collect();
+            self.reasoners
+                .sat
+                .remove_learnt_clauses_with(|lit| eliminated.contains(&lit.variable()));
+            self.reconstruction.extend(reconstruction.clone());
+        }
+        self.stats.preprocessing.add(&preprocessor.stats);
+    }
+
+    /// Returns a copy of the current assignment of the model, typically to save a solution after a call to `solve`.
+    /// Variables eliminated by preprocessing are given a value that is consistent with the original clauses.
+    pub fn solution(&self) -> SavedAssignment {
+        if self.reconstruction.is_empty() {
+            SavedAssignment::from_model(&self.model)
+        } else {
+            self.reconstruction.restore(&self.model)
+        }
+    }
+
+    /// Sets the values that should preferably be given to the variables when they are selected for a decision.
+    /// These values are only hints to the brancher: to restrict the search to the solutions that agree with them,
+    /// they should instead be given as assumptions to [Solver::solve_with_assumptions].
+    pub fn set_default_values(&mut self, values: impl IntoIterator<Item = (VarRef, IntCst)>) {
+        for (var, value) in values {
+            self.brancher.set_default_value(var, value);
+        }
+    }
+
+    /// Requests the given variables to be decided (in order) before any other one, until a first solution is found.
+    /// This only biases the search for the first solution: the order of later decisions is left to the brancher's
+    /// heuristic.
+    pub fn prioritize(&mut self, vars: impl IntoIterator<Item = VarRef>) {
+        for var in vars {
+            self.brancher.prioritize(var);
+        }
+    }
+
+    /// Provides a hint on the values of some variables, e.g. a schedule or plan built by a heuristic method,
+    /// to guide the search towards a good first solution.
+    /// The hinted values become the preferred values of their variables and, if `prioritize` is true,
+    /// the hinted variables are decided first (see [Solver::prioritize]).
+    ///
+    /// Unlike [Solver::warm_start], the hint is not checked and the values of the other variables are not affected:
+    /// the hint should rather cover the variables that determine the others through propagation
+    /// (e.g. the start times of operations in a schedule).
+    pub fn hint(&mut self, values: &[(VarRef, IntCst)], prioritize: bool) {
+        self.set_default_values(values.iter().copied());
+        if prioritize {
+            self.prioritize(values.iter().map(|&(var, _)| var));
+        }
+    }
+
+    /// Uses a (possibly partial) assignment, typically a previous solution imported from
+    /// [LabeledValues](aries_model::assignments::LabeledValues), as the starting point of the search.
+    ///
+    /// The values are first extended into a complete solution by searching under the assumption that they hold,
+    /// with at most [WARM_START_MAX_CONFLICTS] conflicts. If this succeeds, the values of all variables in this
+    /// solution become their preferred values, including the ones of variables introduced by the solver
+    /// (e.g. to reify constraints) that could not appear in `values`.
+    /// Otherwise, only the given values are used as preferred values.
+    /// The search is also subject to the limits of the solver, that should thus be set beforehand.
+    ///
+    /// Returns true if the values could be extended into a solution.
+    pub fn warm_start(&mut self, values: &[(VarRef, IntCst)]) -> bool {
+        self.set_default_values(values.iter().copied());
+        let assumptions: Vec<Bound> = values
+            .iter()
+            .flat_map(|&(var, value)| vec![Bound::geq(var, value), Bound::leq(var, value)])
+            .collect();
+        let limits = self.limits.clone();
+        let max_conflicts = self.stats.num_conflicts + *WARM_START_MAX_CONFLICTS.get();
+        self.limits.max_conflicts = Some(limits.max_conflicts.map_or(max_conflicts, |max| max.min(max_conflicts)));
+        let result = self.solve_with_assumptions(&assumptions);
+        self.limits = limits;
+        let extended = result == SolveResult::Sat;
+        if extended {
+            let solution = self.solution();
+            self.set_default_values(solution.discrete.bound_variables());
+        }
+        self.reset();
+        extended
+    }
+
+    /// Adds the clauses shared by the other solvers of the portfolio since the last import.
+    /// Must be called at the root decision level.
+    fn import_shared_clauses(&mut self) {
+        debug_assert_eq!(self.decision_level, DecLvl::ROOT);
+        if let Some(sharing) = &mut self.sharing {
+            for clause in sharing.import() {
+                self.reasoners.sat.add_forgettable_clause(Disjunction::new(clause));
+            }
+        }
+    }
+
+    /// Returns the assumption that was refuted in the last call to `solve_with_assumptions`, if any.
+    pub fn failed_assumption(&self) -> Option<Bound> {
+        self.failed_assumption
+    }
+
+    /// Returns the subset of the assumptions that are responsible for the failure of
+    /// the last call to `solve_with_assumptions` (empty if the call succeeded).
+    /// It may contain the activation literals of tracked constraints.
+    ///
+    /// The core is not guaranteed to be minimal, see [Solver::minimize_unsat_core].
+    pub fn unsat_core(&self) -> &[Bound] {
+        &self.unsat_core
+    }
+
+    /// Computes the assumptions that imply the negation of the `failed` one.
+    /// The returned core contains `failed` and is ordered as in `assumptions`.
+    ///
+    /// The analysis starts from the negation of the failed assumption and explains it back to the decisions
+    /// that led to it. Because assumptions are decided before anything else, these decisions are
+    /// necessarily assumptions. On return, the solver is back at the root decision level.
+    fn extract_unsat_core(&mut self, failed: Bound, assumptions: &[Bound]) -> Vec<Bound> {
+        let mut explanation = Explanation::new();
+        explanation.push(!failed);
+        let decisions = self.model.discrete.implying_decisions(explanation, &mut self.reasoners);
+        // the model was partially backtracked during analysis, restore all components to a common state
+        self.restore(DecLvl::ROOT);
+        assumptions
+            .iter()
+            .copied()
+            .filter(|&a| a == failed || decisions.contains(&a))
+            .collect()
+    }
+
+    /// Shrinks the unsat core of the last call to `solve_with_assumptions` by removing its assumptions
+    /// one at a time and checking whether the remaining ones are still unsatisfiable (deletion-based minimization).
+    ///
+    /// The returned core is minimal: removing any of its literals would make the problem satisfiable.
+    /// This requires one call to `solve_with_assumptions` per literal of the initial core.
+    /// If the search is stopped by a limit, the minimization is stopped and the returned core might not be minimal.
+    pub fn minimize_unsat_core(&mut self) -> Vec<Bound> {
+        let mut core = self.unsat_core.clone();
+        let mut i = 0;
+        while i < core.len() {
+            let mut candidate = core.clone();
+            candidate.remove(i);
+            match self.search(&candidate) {
+                SolveResult::Sat => {
+                    // the i-th assumption is necessary
+                    i += 1;
+                }
+                SolveResult::Unsat => {
+                    // still unsatisfiable, the new core is an ordered subset of the candidate that
+                    // necessarily retains the first `i` assumptions, which were proven necessary
+                    core = self.unsat_core.clone();
+                }
+                SolveResult::Unknown => break,
+            }
+        }
+        self.unsat_core = core.clone();
+        core
+    }
+
+    /// Returns a minimal set of tracked constraints that cannot be jointly satisfied, identified by their labels.
+    ///
+    /// This is based on the unsat core of the last call to `solve` (or `solve_with_assumptions`) that must
+    /// have failed, and minimizes it with [Solver::minimize_unsat_core].
+    /// If the returned set is empty, then the problem is unsatisfiable regardless of the tracked constraints.
+    pub fn conflicting_constraints(&mut self) -> Vec<Label> {
+        let core = self.minimize_unsat_core();
+        self.tracked
+            .iter()
+            .filter(|(activation, _)| core.contains(activation))
+            .map(|(_, label)| label.clone())
+            .collect()
+    }
+
+    /// Returns an iterator over all solutions of the problem that are distinct when projected on the given `variables`.
+    ///
+    /// After each solution, a clause forbidding the values taken by the `variables` is permanently
+    /// added to the solver, meaning that the solver will remain constrained by it after the enumeration.
+    /// If the `variables` cannot take any other value (e.g. if there are none), no clause is added
+    /// after the last solution, so that the solver does not become unsatisfiable.
+    /// The iteration ends early if the search is interrupted or reaches one of its limits.
+    ///
+    /// Panics if one of the `variables` was eliminated by [Solver::preprocess], as its values could not be blocked.
+    pub fn enumerate(&mut self, variables: &[VarRef]) -> Enumerator<'_> {
+        let eliminated: HashSet<VarRef> = self.reconstruction.eliminated_variables().collect();
+        assert!(
+            variables.iter().all(|var| !eliminated.contains(var)),
+            "Enumerated variables must be frozen when preprocessing"
+        );
+        Enumerator {
+            solver: self,
+            variables: variables.to_vec(),
+            exhausted: false,
+        }
+    }
+
+    /// Adds a clause forbidding the current values of the given variables, that must all be bound.
+    /// The solver is reset to the root decision level to integrate the clause.
+    ///
+    /// Returns false if the clause is empty, i.e., there is no other possible value for the variables.
+    /// In this case, no clause is added as it would make the problem unsatisfiable.
+    fn add_blocking_clause(&mut self, variables: &[VarRef]) -> bool {
+        let values: Vec<(VarRef, IntCst)> = variables
+            .iter()
+            .map(|&v| {
+                let (lb, ub) = self.model.discrete.domain_of(v);
+                debug_assert_eq!(lb, ub, "Variable is not bound in solution");
+                (v, lb)
+            })
+            .collect();
+        self.reset();
+        // at least one variable must take a different value: (v < value) || (v > value)
+        let mut clause = Vec::with_capacity(values.len() * 2);
+        for (v, value) in values {
+            let (lb, ub) = self.model.discrete.domain_of(v);
+            if lb < value {
+                clause.push(Bound::leq(v, value - 1));
+            }
+            if value < ub {
+                clause.push(Bound::geq(v, value + 1));
+            }
+        }
+        if clause.is_empty() {
+            // the blocking clause is violated at the root level, the enumeration is over
+            false
+        } else {
+            self.reasoners.sat.add_clause(Disjunction::new(clause));
+            true
+        }
+    }
+
     pub fn minimize(&mut self, objective: impl Into<IAtom>) -> Option<(IntCst, SavedAssignment)> {
         self.minimize_with(objective, |_, _| ())
     }
 
+    /// Minimizes the objective with the strategy set by the `ARIES_SMT_OPTIMIZATION_STRATEGY` environment variable
+    /// (linear search by default). The `on_new_solution` callback is invoked on each improving solution.
+    ///
+    /// Returns the best solution found, if any. See [Solver::minimize_with_strategy] for more detailed results.
     pub fn minimize_with(
         &mut self,
         objective: impl Into<IAtom>,
         mut on_new_solution: impl FnMut(IntCst, &SavedAssignment),
     ) -> Option<(IntCst, SavedAssignment)> {
+        self.minimize_with_strategy(objective, *OPTIMIZATION_STRATEGY.get(), |bounds, sol| {
+            if let Some(sol) = sol {
+                on_new_solution(bounds.ub, sol)
+            }
+        })
+        .into_best()
+    }
+
+    /// Minimizes the objective using the given optimization strategy.
+    ///
+    /// The `on_progress` callback is invoked each time the interval of possible values of the optimum is tightened,
+    /// together with the new solution when the tightening results from finding one.
+    ///
+    /// Note that the solver remains constrained by the bounds proven on the objective.
+    pub fn minimize_with_strategy(
+        &mut self,
+        objective: impl Into<IAtom>,
+        strategy: OptimizationStrategy,
+        mut on_progress: impl FnMut(ObjectiveBounds, Option<&SavedAssignment>),
+    ) -> OptimizationResult {
         let objective = objective.into();
-        let mut result = None;
-        while self.solve() {
-            let lb = self.model.domain_of(objective).0;
-
-            let sol = SavedAssignment::from_model(&self.model);
-            if *OPTIMIZE_USES_LNS.get() {
-                // LNS requested, set the default values of all variables to the one of
-                // the best solution. As a result, the solver will explore the solution space
-                // around the incumbent solution, only pushed away by the learnt clauses.
-                self.brancher.set_default_values_from(&self.model);
+        let var = match objective.var {
+            Some(var) => VarRef::from(var),
+            None => {
+                // constant objective, any solution is optimal
+                return match self.solve() {
+                    SolveResult::Sat => {
+                        let solution = self.solution();
+                        let bounds = ObjectiveBounds::new(objective.shift, objective.shift);
+                        self.notify_objective_bounds(bounds, Some(&solution));
+                        on_progress(bounds, Some(&solution));
+                        OptimizationResult::Optimal {
+                            value: objective.shift,
+                            solution,
+                        }
+                    }
+                    SolveResult::Unsat => OptimizationResult::Infeasible,
+                    SolveResult::Unknown => OptimizationResult::Unknown {
+                        lower_bound: objective.shift,
+                    },
+                };
             }
-            on_new_solution(lb, &sol);
-            result = Some((lb, sol));
-            self.stats.num_restarts += 1;
-            self.reset();
-            let improved = self.model.lt(objective, lb);
-            self.enforce_all(&[improved]);
+        };
+        // literal that holds iff the objective is less than or equal to the given value
+        let objective_leq = |value: IntCst| Bound::leq(var, value - objective.shift);
+
+        self.reset();
+        if !self.propagate_and_backtrack_to_consistent() {
+            return OptimizationResult::Infeasible;
+        }
+        let (lb, ub) = self.model.domain_of(objective);
+        let mut bounds = ObjectiveBounds::new(lb, ub);
+        let mut best: Option<(IntCst, SavedAssignment)> = None;
+        // true if the search was stopped before proving optimality
+        let mut stopped = false;
+
+        loop {
+            // the assumption to make on the objective for the next call, if any
+            let assumption = match strategy {
+                OptimizationStrategy::Linear => None,
+                OptimizationStrategy::Dichotomic if best.is_none() => None,
+                OptimizationStrategy::Dichotomic => Some(objective_leq(bounds.lb + (bounds.ub - bounds.lb - 1) / 2)),
+                OptimizationStrategy::LowerBoundLinear => Some(objective_leq(bounds.lb)),
+            };
+            let assumptions: Vec<Bound> = assumption.iter().copied().collect();
+
+            let result = self.solve_with_assumptions(&assumptions);
+            if result == SolveResult::Unknown {
+                stopped = true;
+                break;
+            } else if result == SolveResult::Sat {
+                let value = self.model.domain_of(objective).0;
+                let sol = self.solution();
+                self.brancher.solution_found(&self.model);
+                if let Some(sharing) = &self.sharing {
+                    // other solvers of the portfolio can restrict themselves to strictly better solutions
+                    sharing.export(&[objective_leq(value - 1)]);
+                }
+                bounds.ub = value;
+                self.notify_objective_bounds(bounds, Some(&sol));
+                on_progress(bounds, Some(&sol));
+                best = Some((value, sol));
+                self.stats.num_restarts += 1;
+                if bounds.lb >= bounds.ub {
+                    break;
+                }
+                // from now on, only look for strictly better solutions
+                let improved = self.model.lt(objective, value);
+                self.enforce_all(&[improved]);
+            } else if let Some(assumption) = assumption.filter(|a| self.unsat_core.contains(a)) {
+                // the assumption on the objective is responsible for the failure, its negation is a new lower bound
+                if let Some(sharing) = &self.sharing {
+                    sharing.export(&[!assumption]);
+                }
+                let lower_bounded = BAtom::from(!assumption);
+                self.enforce_all(&[lower_bounded]);
+                // propagate at the root, which might further improve the lower bound
+                if !self.propagate_and_backtrack_to_consistent() {
+                    break;
+                }
+                bounds.lb = self.model.domain_of(objective).0.min(bounds.ub);
+                self.notify_objective_bounds(bounds, None);
+                on_progress(bounds, None);
+                if best.is_some() && bounds.lb >= bounds.ub {
+                    break;
+                }
+            } else {
+                // no (better) solution regardless of the value of the objective
+                break;
+            }
+        }
+        match best {
+            Some((value, solution)) if stopped => OptimizationResult::Feasible {
+                value,
+                solution,
+                lower_bound: bounds.lb,
+            },
+            Some((value, solution)) => OptimizationResult::Optimal { value, solution },
+            None if stopped => OptimizationResult::Unknown { lower_bound: bounds.lb },
+            None => OptimizationResult::Infeasible,
+        }
+    }
+
+    fn notify_objective_bounds(&mut self, bounds: ObjectiveBounds, solution: Option<&SavedAssignment>) {
+        for monitor in &mut self.monitors {
+            monitor.on_objective_bounds(bounds, solution, &self.stats);
         }
-        result
     }
 
     pub fn decide(&mut self, decision: Bound) {
@@ -244,24 +849,77 @@ impl Solver {
         }
     }
 
+    /// Returns the level to backtrack to when learning the violated `clause`, whose backjump level is `backjump_level`.
+    ///
+    /// With chronological backtracking (see [Solver::set_chrono_backtrack_threshold]), a backjump that would undo too many
+    /// decision levels is replaced by a backtrack to the level just before the latest literal of the clause was
+    /// falsified, where the clause is still unit. The literal it asserts is then set at a higher level than
+    /// necessary and will be lost (until the clause propagates again) when backtracking below this level.
+    fn chronological_backtrack_level(&self, clause: &[Bound], backjump_level: DecLvl) -> DecLvl {
+        let threshold = self.chrono_backtrack_threshold;
+        if threshold == 0 {
+            return backjump_level;
+        }
+        let conflict_level = clause
+            .iter()
+            .filter_map(|&lit| self.model.discrete.implying_event(!lit))
+            .map(|ev| self.model.discrete.trail().decision_level(ev))
+            .max()
+            .unwrap_or(DecLvl::ROOT);
+        if conflict_level.to_int() - backjump_level.to_int() > threshold {
+            conflict_level - 1
+        } else {
+            backjump_level
+        }
+    }
+
+    /// Computes the Literal Block Distance (LBD) of a violated clause: the number of distinct
+    /// non-root decision levels at which its literals were falsified.
+    fn lbd(&self, clause: &[Bound]) -> u32 {
+        sat_solver::lbd(clause.iter().copied(), &self.model.discrete)
+    }
+
     /// Integrates a conflicting clause (typically learnt through propagation)
     /// and backtracks to the appropriate level.
     /// As a side effect, the activity of the variables in the clause will be increased.
     /// Returns an error if there is no level at which the clause is not conflicting.
     #[must_use]
     fn add_conflicting_clause_and_backtrack(&mut self, expl: Disjunction) -> bool {
-        if let Some(dl) = self.backtrack_level_for_clause(expl.literals()) {
+        let expl = if *MINIMIZE_LEARNT_CLAUSES.get() {
+            let len = expl.literals().len();
+            let minimized = self.model.discrete.minimize_clause(expl, &mut self.reasoners);
+            self.stats.num_minimized_literals += (len - minimized.literals().len()) as u64;
+            minimized
+        } else {
+            expl
+        };
+        if let Some(backjump_level) = self.backtrack_level_for_clause(expl.literals()) {
+            let dl = self.chronological_backtrack_level(expl.literals(), backjump_level);
+            if dl != backjump_level {
+                self.stats.num_chrono_backtracks += 1;
+            }
+            // the LBD must be computed before backtracking, while all literals of the clause are still violated
+            let lbd = self.lbd(expl.literals());
+            self.brancher.clause_learnt(expl.literals(), lbd);
+
             // backtrack
             self.restore(dl);
             debug_assert_eq!(self.model.discrete.or_value(expl.literals()), None);
+            for monitor in &mut self.monitors {
+                monitor.on_conflict(expl.literals(), dl, &self.stats);
+            }
 
             // bump activity of all variables of the clause
             for b in expl.literals() {
                 self.brancher.bump_activity(b.variable());
             }
 
+            if let Some(sharing) = &self.sharing {
+                sharing.export(expl.literals());
+            }
+
             // add clause to sat solver
-            self.reasoners.sat.add_forgettable_clause(expl);
+            self.reasoners.sat.add_learnt_clause(expl, lbd);
 
             true
         } else {
@@ -269,8 +927,19 @@ impl Solver {
         }
     }
 
+    /// Records that the problem was proven unsatisfiable, independently of any assumption.
+    fn set_unsat(&mut self) {
+        self.proven_unsat = true;
+        if let Some(proof) = self.reasoners.sat.proof_mut() {
+            proof.add_empty_clause();
+        }
+    }
+
     #[must_use]
     pub fn propagate_and_backtrack_to_consistent(&mut self) -> bool {
+        if self.proven_unsat {
+            return false;
+        }
         let global_start = StartCycleCount::now();
         loop {
             let num_events_at_start = self.model.discrete.num_events();
@@ -290,6 +959,7 @@ impl Solver {
                         continue;
                     } else {
                         // no level at which the clause is not violated
+                        self.set_unsat();
                         self.stats.propagation_time += global_start.elapsed();
                         self.stats.per_module_propagation_time[0] += sat_start.elapsed();
                         return false; // UNSAT
@@ -326,6 +996,7 @@ impl Solver {
                             // skip the rest of the propagations
                             break;
                         } else {
+                            self.set_unsat();
                             return false;
                         }
                     }
@@ -352,8 +1023,21 @@ impl Solver {
         true
     }
 
+    /// All statistics of the solver, including the ones of the SAT solver and of each theory,
+    /// in a machine-readable record.
+    pub fn stats_record(&self) -> StatsRecord {
+        let mut record = self.stats.record();
+        record.extend_prefixed("sat", self.reasoners.sat.stats_entries());
+        for (i, th) in self.reasoners.theories.iter().enumerate() {
+            record.extend_prefixed(&module_name(i + 1), th.stats());
+        }
+        record
+    }
+
     pub fn print_stats(&self) {
         println!("{}", self.stats);
+        println!("====== SAT");
+        self.reasoners.sat.print_stats();
         for (i, th) in self.reasoners.theories.iter().enumerate() {
             println!("====== Theory({})", i + 1);
             th.print_stats();
@@ -395,6 +1079,11 @@ impl Backtrack for Solver {
     }
 
     fn restore(&mut self, saved_id: DecLvl) {
+        if saved_id < self.decision_level {
+            for monitor in &mut self.monitors {
+                monitor.on_backtrack(saved_id);
+            }
+        }
         self.decision_level = saved_id;
         self.model.restore(saved_id);
         self.brancher.restore(saved_id);
@@ -406,6 +1095,30 @@ impl Backtrack for Solver {
     }
 }
 
+/// Iterator over the solutions of a solver, see [Solver::enumerate].
+pub struct Enumerator<'a> {
+    solver: &'a mut Solver,
+    variables: Vec<VarRef>,
+    exhausted: bool,
+}
+
+impl Iterator for Enumerator<'_> {
+    type Item = SavedAssignment;
+
+    fn next(&mut self) -> Option<SavedAssignment> {
+        if self.exhausted || self.solver.solve() != SolveResult::Sat {
+            self.exhausted = true;
+            return None;
+        }
+        let solution = self.solver.solution();
+        if !self.solver.add_blocking_clause(&self.variables) {
+            // no other values possible for the variables, this is the last solution
+            self.exhausted = true;
+        }
+        Some(solution)
+    }
+}
+
 // TODO: is this needed
 #[derive(Copy, Clone)]
 pub struct Binding {
@@ -418,6 +1131,17 @@ impl Binding {
     }
 }
 
+/// Outcome of a search for a solution.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SolveResult {
+    /// A solution was found and is available in the model of the solver.
+    Sat,
+    /// The problem has no solution (under the given assumptions).
+    Unsat,
+    /// The search was interrupted or reached one of its limits before reaching a conclusion.
+    Unknown,
+}
+
 pub enum EnforceResult {
     Enforced,
     Reified(Bound),
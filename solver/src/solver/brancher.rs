@@ -1,4 +1,6 @@
+use crate::solver::restarts::{luby, LbdAverages, RestartPolicy, GLUCOSE_FACTOR, GLUCOSE_WINDOW, RESTART_POLICY};
 use crate::solver::stats::Stats;
+use crate::solver::OPTIMIZE_USES_LNS;
 use aries_backtrack::{Backtrack, DecLvl, Trail};
 use aries_collections::heap::IdxHeap;
 use aries_model::assignments::Assignment;
@@ -17,28 +19,84 @@ pub static INITIALLY_ALLOWED_CONFLICTS: EnvParam<u64> = EnvParam::new("ARIES_SMT
 pub static INCREASE_RATIO_FOR_ALLOWED_CONFLICTS: EnvParam<f32> =
     EnvParam::new("ARIES_SMT_INCREASE_RATIO_FOR_ALLOWED_CONFLICTS", "1.5");
 
+#[derive(Clone, Debug)]
 pub struct BranchingParams {
     pub prefer_min_value: bool,
+    pub restart_policy: RestartPolicy,
+    /// Number of conflicts before the first restart (geometric restarts) or unit of the Luby sequence (luby restarts).
     pub allowed_conflicts: u64,
     pub increase_ratio_for_allowed_conflicts: f32,
+    /// If true, the preferred value of each variable is set to its value in the best solution found
+    /// during optimization (Large Neighborhood Search like behavior).
+    pub lns: bool,
 }
 
 impl Default for BranchingParams {
     fn default() -> Self {
         BranchingParams {
             prefer_min_value: *PREFER_MIN_VALUE.get(),
+            restart_policy: *RESTART_POLICY.get(),
             allowed_conflicts: *INITIALLY_ALLOWED_CONFLICTS.get(),
             increase_ratio_for_allowed_conflicts: *INCREASE_RATIO_FOR_ALLOWED_CONFLICTS.get(),
+            lns: *OPTIMIZE_USES_LNS.get(),
         }
     }
 }
 
-pub struct Brancher {
+/// A branching heuristic, responsible for selecting the decisions made by the solver.
+///
+/// The brancher is saved and restored together with the solver, so that it can maintain
+/// backtrackable data structures.
+pub trait Brancher: Backtrack {
+    /// Returns the next decision to make, or `None` if no decision is left to be made,
+    /// meaning that all variables of the model are bound.
+    ///
+    /// The brancher is also responsible for deciding when the solver should restart.
+    fn next_decision(&mut self, stats: &Stats, model: &Model) -> Option<Decision>;
+
+    /// Invoked on each variable of a learnt clause, as it might be worth prioritizing it in the next decisions.
+    fn bump_activity(&mut self, _var: VarRef) {}
+
+    /// Invoked on each clause learnt from a conflict, with its Literal Block Distance (LBD), i.e.,
+    /// the number of distinct decision levels of its literals.
+    fn clause_learnt(&mut self, _clause: &[Bound], _lbd: u32) {}
+
+    /// Policy used to decide when to restart, if any. This is only used for reporting.
+    fn restart_policy(&self) -> Option<RestartPolicy> {
+        None
+    }
+
+    /// Invoked each time a new solution is found during optimization.
+    fn solution_found(&mut self, _assignment: &Model) {}
+
+    /// Sets the value that should preferably be given to the variable when it is selected for a decision.
+    /// Branchers that do not support preferred values may ignore it.
+    fn set_default_value(&mut self, _var: VarRef, _value: IntCst) {}
+
+    /// Requests the variable to be considered before the non-prioritized ones in the first decisions.
+    /// Branchers that do not support it may ignore it.
+    fn prioritize(&mut self, _var: VarRef) {}
+}
+
+/// Default brancher, that selects the variable with the highest activity (VSIDS-like heuristic) and
+/// sets it to its preferred value.
+pub struct ActivityBrancher {
     pub params: BranchingParams,
     heap: VarSelect,
     default_assignment: DefaultValues,
     conflicts_at_last_restart: u64,
+    /// Number of restarts triggered by this brancher, used as an index in the Luby sequence.
+    num_restarts: u64,
+    /// LBD of the learnt clauses, used by the glucose restart policy.
+    lbds: LbdAverages,
     num_processed_var: usize,
+    /// Variables to decide, in order, before any other one. They are only considered until the first solution,
+    /// after which the order is only determined by activities.
+    prioritized: Vec<VarRef>,
+    /// Number of prioritized variables that are known to be bound, i.e., index of the first one to consider.
+    num_bound_prioritized: usize,
+    /// Previous values of `num_bound_prioritized`, to rewind it when backtracking.
+    prioritized_trail: Trail<usize>,
 }
 
 #[derive(Default)]
@@ -51,14 +109,23 @@ pub enum Decision {
     Restart,
 }
 
-impl Brancher {
+impl ActivityBrancher {
     pub fn new() -> Self {
-        Brancher {
-            params: Default::default(),
+        Self::with_params(Default::default())
+    }
+
+    pub fn with_params(params: BranchingParams) -> Self {
+        ActivityBrancher {
+            params,
             heap: VarSelect::new(Default::default()),
             default_assignment: DefaultValues::default(),
             conflicts_at_last_restart: 0,
+            num_restarts: 0,
+            lbds: LbdAverages::new(*GLUCOSE_WINDOW.get()),
             num_processed_var: 0,
+            prioritized: Vec::new(),
+            num_bound_prioritized: 0,
+            prioritized_trail: Trail::default(),
         }
     }
 
@@ -73,6 +140,31 @@ impl Brancher {
         self.num_processed_var += count;
     }
 
+    /// Returns true if the restart policy requires a restart.
+    fn restart_required(&self, stats: &Stats) -> bool {
+        let conflicts_since_restart = stats.num_conflicts - self.conflicts_at_last_restart;
+        match self.params.restart_policy {
+            RestartPolicy::Geometric => conflicts_since_restart >= self.params.allowed_conflicts,
+            RestartPolicy::Luby => {
+                conflicts_since_restart >= self.params.allowed_conflicts * luby(self.num_restarts + 1)
+            }
+            RestartPolicy::Glucose => self.lbds.recent_above_average(*GLUCOSE_FACTOR.get()),
+        }
+    }
+
+    pub fn set_default_value(&mut self, var: VarRef, val: IntCst) {
+        self.default_assignment.bools.insert(var, val);
+    }
+
+    pub fn set_default_values_from(&mut self, assignment: &Model) {
+        self.import_vars(assignment);
+        for (var, val) in assignment.discrete.bound_variables() {
+            self.set_default_value(var, val);
+        }
+    }
+}
+
+impl Brancher for ActivityBrancher {
     /// Select the next decision to make while maintaining the invariant that every non bound variable remains in the queue.
     ///
     /// This invariant allows to invoke this function at the decision level preceding the one of the decision that will be returned.
@@ -80,7 +172,7 @@ impl Brancher {
     /// to the level preceding the decision to be made.
     ///
     /// Returns `None` if no decision is left to be made.
-    pub fn next_decision(&mut self, stats: &Stats, model: &Model) -> Option<Decision> {
+    fn next_decision(&mut self, stats: &Stats, model: &Model) -> Option<Decision> {
         self.import_vars(model);
 
         let mut popper = self.heap.extractor();
@@ -106,15 +198,32 @@ impl Brancher {
             }
         };
         if let Some(v) = next_unset {
-            if stats.num_conflicts - self.conflicts_at_last_restart >= self.params.allowed_conflicts {
-                // we have exceeded the number of allowed conflict, time for a restart
+            if self.restart_required(stats) {
                 self.conflicts_at_last_restart = stats.num_conflicts;
-                // increase the number of allowed conflicts
-                self.params.allowed_conflicts =
-                    (self.params.allowed_conflicts as f32 * self.params.increase_ratio_for_allowed_conflicts) as u64;
+                self.num_restarts += 1;
+                match self.params.restart_policy {
+                    RestartPolicy::Geometric => {
+                        // increase the number of allowed conflicts
+                        self.params.allowed_conflicts = (self.params.allowed_conflicts as f32
+                            * self.params.increase_ratio_for_allowed_conflicts)
+                            as u64;
+                    }
+                    RestartPolicy::Luby => {}
+                    RestartPolicy::Glucose => self.lbds.clear_recent(),
+                }
 
                 Some(Decision::Restart)
             } else {
+                // variables explicitly prioritized are decided first, skipping the ones known to be bound
+                let first_unbound = self.prioritized[self.num_bound_prioritized..]
+                    .iter()
+                    .position(|&var| !model.discrete.domains.is_bound(var))
+                    .map_or(self.prioritized.len(), |i| self.num_bound_prioritized + i);
+                if first_unbound != self.num_bound_prioritized {
+                    self.prioritized_trail.push(self.num_bound_prioritized);
+                    self.num_bound_prioritized = first_unbound;
+                }
+                let v = self.prioritized.get(first_unbound).copied().unwrap_or(v);
                 // determine value for literal:
                 // - first from per-variable preferred assignments
                 // - otherwise from the preferred value for boolean variables
@@ -151,25 +260,46 @@ impl Brancher {
         }
     }
 
-    pub fn set_default_value(&mut self, var: VarRef, val: IntCst) {
-        self.default_assignment.bools.insert(var, val);
+    /// Increase the activity of the variable and perform an reordering in the queue.
+    /// The activity is then used to select the next variable.
+    fn bump_activity(&mut self, bvar: VarRef) {
+        // the variable might not have been imported yet if no decision was requested since its creation
+        // (e.g. a conflict occurred while deciding assumptions), in which case it will be imported with a default activity
+        if self.heap.is_declared(bvar) {
+            self.heap.var_bump_activity(bvar);
+        }
     }
 
-    pub fn set_default_values_from(&mut self, assignment: &Model) {
-        self.import_vars(assignment);
-        for (var, val) in assignment.discrete.bound_variables() {
-            self.set_default_value(var, val);
+    fn clause_learnt(&mut self, _clause: &[Bound], lbd: u32) {
+        self.lbds.add(lbd);
+    }
+
+    fn restart_policy(&self) -> Option<RestartPolicy> {
+        Some(self.params.restart_policy)
+    }
+
+    fn solution_found(&mut self, assignment: &Model) {
+        self.prioritized.clear();
+        self.num_bound_prioritized = 0;
+        if self.params.lns {
+            // LNS requested, set the default values of all variables to the one of
+            // the best solution. As a result, the solver will explore the solution space
+            // around the incumbent solution, only pushed away by the learnt clauses.
+            self.set_default_values_from(assignment);
         }
     }
 
-    /// Increase the activity of the variable and perform an reordering in the queue.
-    /// The activity is then used to select the next variable.
-    pub fn bump_activity(&mut self, bvar: VarRef) {
-        self.heap.var_bump_activity(bvar);
+    fn set_default_value(&mut self, var: VarRef, value: IntCst) {
+        ActivityBrancher::set_default_value(self, var, value)
+    }
+
+    /// The variable is decided before any non-prioritized one, until the first solution is found.
+    fn prioritize(&mut self, var: VarRef) {
+        self.prioritized.push(var);
     }
 }
 
-impl Default for Brancher {
+impl Default for ActivityBrancher {
     fn default() -> Self {
         Self::new()
     }
@@ -346,8 +476,9 @@ impl<'a> Popper<'a> {
     }
 }
 
-impl Backtrack for Brancher {
+impl Backtrack for ActivityBrancher {
     fn save_state(&mut self) -> DecLvl {
+        self.prioritized_trail.save_state();
         self.heap.save_state()
     }
 
@@ -356,6 +487,11 @@ impl Backtrack for Brancher {
     }
 
     fn restore_last(&mut self) {
+        // the list of prioritized variables might have been cleared since the events were recorded,
+        // in which case they should not move the index forward
+        let num_bound_prioritized = &mut self.num_bound_prioritized;
+        self.prioritized_trail
+            .restore_last_with(|previous| *num_bound_prioritized = previous.min(*num_bound_prioritized));
         self.heap.restore_last()
     }
 }
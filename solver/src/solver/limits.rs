@@ -0,0 +1,52 @@
+use crate::solver::stats::Stats;
+use std::time::{Duration, Instant};
+
+/// Number of decisions between two checks of the deadline, to avoid reading the clock at each step of the search.
+const DEADLINE_CHECK_PERIOD: u64 = 1024;
+
+/// Limits on the search effort of the solver. When any of them is reached, the search is
+/// stopped and an `Unknown` outcome is reported.
+///
+/// Conflict and decision limits apply to the total counts of the solver, as reported by its [Stats],
+/// and thus span multiple calls to `solve` (e.g. during optimization).
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    /// Instant after which the search should be stopped. As it is only checked periodically,
+    /// the search might go on for a few more decisions after it.
+    pub deadline: Option<Instant>,
+    /// Maximum number of conflicts (`Stats::num_conflicts`).
+    pub max_conflicts: Option<u64>,
+    /// Maximum number of decisions (`Stats::num_decisions`).
+    pub max_decisions: Option<u64>,
+}
+
+impl SearchLimits {
+    /// Returns a set of limits that never stops the search.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Sets a deadline `timeout` after the current time.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.deadline = Some(Instant::now() + timeout);
+        self
+    }
+
+    pub fn with_max_conflicts(mut self, max_conflicts: u64) -> Self {
+        self.max_conflicts = Some(max_conflicts);
+        self
+    }
+
+    pub fn with_max_decisions(mut self, max_decisions: u64) -> Self {
+        self.max_decisions = Some(max_decisions);
+        self
+    }
+
+    /// Returns true if any of the limits has been reached.
+    pub fn exceeded(&self, stats: &Stats) -> bool {
+        matches!(self.max_conflicts, Some(max) if stats.num_conflicts >= max)
+            || matches!(self.max_decisions, Some(max) if stats.num_decisions >= max)
+            || matches!(self.deadline, Some(deadline)
+                if stats.num_decisions.is_multiple_of(DEADLINE_CHECK_PERIOD) && Instant::now() >= deadline)
+    }
+}
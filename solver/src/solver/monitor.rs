@@ -0,0 +1,95 @@
+//! Observation of the search of a [Solver](crate::solver::Solver) through callbacks.
+//!
+//! Monitors are registered with `Solver::add_monitor` and notified of the main events of the search.
+//! They can be used to report progress, record a trace of the search or implement custom stopping criteria.
+
+use crate::solver::optimization::ObjectiveBounds;
+use crate::solver::stats::Stats;
+use aries_backtrack::DecLvl;
+use aries_model::assignments::SavedAssignment;
+use aries_model::bounds::Bound;
+use aries_model::Model;
+use std::time::{Duration, Instant};
+
+/// Callbacks invoked by the solver on the events of the search. All of them do nothing by default.
+pub trait SearchMonitor {
+    /// Invoked after a decision made by the brancher, that opened the decision level `level`.
+    /// Assumptions are not reported.
+    fn on_decision(&mut self, _decision: Bound, _level: DecLvl, _stats: &Stats) {}
+
+    /// Invoked on each conflict with the clause learnt from it, after backjumping to `backtrack_level`.
+    /// Conflicts from which no clause can be learnt (i.e., when the problem is proven unsatisfiable) are not reported.
+    fn on_conflict(&mut self, _learnt: &[Bound], _backtrack_level: DecLvl, _stats: &Stats) {}
+
+    /// Invoked on each restart requested by the brancher.
+    fn on_restart(&mut self, _stats: &Stats) {}
+
+    /// Invoked each time the search finds a solution, which is the current assignment of `model`.
+    fn on_solution(&mut self, _model: &Model, _stats: &Stats) {}
+
+    /// Invoked during an optimization each time the interval of possible values of the optimum is tightened,
+    /// together with the new solution when the tightening results from finding one.
+    fn on_objective_bounds(&mut self, _bounds: ObjectiveBounds, _solution: Option<&SavedAssignment>, _stats: &Stats) {}
+
+    /// Invoked each time the solver backtracks to the decision level `level`.
+    fn on_backtrack(&mut self, _level: DecLvl) {}
+
+    /// Invoked before each decision: if it returns true, the search is stopped with an `Unknown` outcome,
+    /// as when reaching one of the search limits.
+    fn should_stop(&mut self, _stats: &Stats) -> bool {
+        false
+    }
+}
+
+/// Monitor that periodically prints the progress of the search on the standard error.
+pub struct ProgressReport {
+    /// Minimum duration between two reports.
+    period: Duration,
+    start: Instant,
+    last_report: Instant,
+    num_solutions: u64,
+}
+
+impl ProgressReport {
+    pub fn new(period: Duration) -> Self {
+        let now = Instant::now();
+        ProgressReport {
+            period,
+            start: now,
+            last_report: now,
+            num_solutions: 0,
+        }
+    }
+
+    fn report(&mut self, stats: &Stats) {
+        self.last_report = Instant::now();
+        eprintln!(
+            "[{:>8.3}s] decisions: {:>10}  conflicts: {:>9}  restarts: {:>6}  solutions: {:>4}",
+            self.start.elapsed().as_secs_f64(),
+            stats.num_decisions,
+            stats.num_conflicts,
+            stats.num_restarts,
+            self.num_solutions
+        );
+    }
+}
+
+impl SearchMonitor for ProgressReport {
+    fn on_decision(&mut self, _decision: Bound, _level: DecLvl, stats: &Stats) {
+        // checking the time on each decision would be too expensive
+        if stats.num_decisions.is_multiple_of(1024) && self.last_report.elapsed() >= self.period {
+            self.report(stats);
+        }
+    }
+
+    fn on_restart(&mut self, stats: &Stats) {
+        if self.last_report.elapsed() >= self.period {
+            self.report(stats);
+        }
+    }
+
+    fn on_solution(&mut self, _model: &Model, stats: &Stats) {
+        self.num_solutions += 1;
+        self.report(stats);
+    }
+}
@@ -0,0 +1,112 @@
+use aries_model::assignments::SavedAssignment;
+use aries_model::lang::IntCst;
+use env_param::EnvParam;
+use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
+
+/// Strategy used by `Solver::minimize_with` when none is explicitly provided.
+pub static OPTIMIZATION_STRATEGY: EnvParam<OptimizationStrategy> =
+    EnvParam::new("ARIES_SMT_OPTIMIZATION_STRATEGY", "linear");
+
+/// Strategy used to search for an optimal solution.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OptimizationStrategy {
+    /// Repeatedly look for a solution that is strictly better than the best one found so far,
+    /// until none can be found.
+    Linear,
+    /// Binary search over the interval of possible values of the objective. Each step assumes that
+    /// the objective is below the middle of the interval and updates the lower or upper bound
+    /// depending on whether the solver succeeds.
+    Dichotomic,
+    /// Linear search from below: look for a solution whose value is the current lower bound of the objective.
+    /// Each failure whose unsat core involves the bound proves a new lower bound. The first solution found is optimal.
+    LowerBoundLinear,
+}
+
+impl FromStr for OptimizationStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(OptimizationStrategy::Linear),
+            "dichotomic" | "binary" => Ok(OptimizationStrategy::Dichotomic),
+            "lb-linear" | "lower-bound" => Ok(OptimizationStrategy::LowerBoundLinear),
+            _ => Err(format!("Unknown optimization strategy: {}", s)),
+        }
+    }
+}
+
+impl Display for OptimizationStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            OptimizationStrategy::Linear => write!(f, "linear"),
+            OptimizationStrategy::Dichotomic => write!(f, "dichotomic"),
+            OptimizationStrategy::LowerBoundLinear => write!(f, "lb-linear"),
+        }
+    }
+}
+
+/// Interval in which the optimal value of the objective is known to lie.
+///
+/// The lower bound is proven: no solution has a value below it.
+/// The upper bound is either the value of the best solution found so far or, if none was found,
+/// the upper bound of the objective's domain.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ObjectiveBounds {
+    pub lb: IntCst,
+    pub ub: IntCst,
+}
+
+impl ObjectiveBounds {
+    pub fn new(lb: IntCst, ub: IntCst) -> Self {
+        ObjectiveBounds { lb, ub }
+    }
+}
+
+impl Display for ObjectiveBounds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "[{}, {}]", self.lb, self.ub)
+    }
+}
+
+/// Result of an optimization procedure.
+pub enum OptimizationResult {
+    /// The solution has been proven optimal.
+    Optimal { value: IntCst, solution: SavedAssignment },
+    /// A solution was found, but the search was stopped before proving its optimality.
+    /// The optimal value is known to be in `[lower_bound, value]`.
+    Feasible {
+        value: IntCst,
+        solution: SavedAssignment,
+        lower_bound: IntCst,
+    },
+    /// The problem has been proven to have no solution.
+    Infeasible,
+    /// The search was stopped before any solution was found or infeasibility was proven.
+    /// No solution can have a value below `lower_bound`.
+    Unknown { lower_bound: IntCst },
+}
+
+impl OptimizationResult {
+    pub fn is_optimal(&self) -> bool {
+        matches!(self, OptimizationResult::Optimal { .. })
+    }
+
+    /// Returns the value and the assignment of the best solution found, if any.
+    pub fn best(&self) -> Option<(IntCst, &SavedAssignment)> {
+        match self {
+            OptimizationResult::Optimal { value, solution } => Some((*value, solution)),
+            OptimizationResult::Feasible { value, solution, .. } => Some((*value, solution)),
+            _ => None,
+        }
+    }
+
+    /// Returns the value and the assignment of the best solution found, if any.
+    pub fn into_best(self) -> Option<(IntCst, SavedAssignment)> {
+        match self {
+            OptimizationResult::Optimal { value, solution } => Some((value, solution)),
+            OptimizationResult::Feasible { value, solution, .. } => Some((value, solution)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,399 @@
+//! Portfolio of solvers running in parallel on the same problem.
+//!
+//! Each worker is a full [Solver] built from a clone of the same model and constraints, but searching
+//! with its own [BranchingParams]. Workers exchange short learnt clauses and the bounds they prove on the
+//! objective. The first worker to reach a conclusive result stops all others.
+
+use crate::solver::brancher::{ActivityBrancher, Brancher, BranchingParams};
+use crate::solver::limits::SearchLimits;
+use crate::solver::monitor::SearchMonitor;
+use crate::solver::optimization::{ObjectiveBounds, OptimizationResult, OptimizationStrategy};
+use crate::solver::stats::Stats;
+use crate::solver::{SolveResult, Solver};
+use crate::Theory;
+use aries_model::assignments::{Assignment, SavedAssignment};
+use aries_model::bounds::Bound;
+use aries_model::lang::{BAtom, IAtom, IntCst, VarRef};
+use aries_model::Model;
+use env_param::EnvParam;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+
+/// Maximum number of literals of a learnt clause for it to be shared with the other workers.
+pub static MAX_SHARED_CLAUSE_LENGTH: EnvParam<usize> = EnvParam::new("ARIES_SMT_MAX_SHARED_CLAUSE_LENGTH", "8");
+
+/// Log of all clauses shared by the workers of a portfolio, together with the worker that produced them.
+type SharedClauses = Arc<Mutex<Vec<(usize, Vec<Bound>)>>>;
+
+/// End of a solver's connection to the clauses shared in a portfolio.
+pub(crate) struct ClauseSharing {
+    /// Index of the worker owning this end.
+    worker: usize,
+    clauses: SharedClauses,
+    /// Index of the first clause of the log that has not been imported yet.
+    next_import: usize,
+    /// Variables from this one onwards were created by the solver itself (e.g. when enforcing a bound
+    /// on the objective) and have no meaning in the other workers.
+    first_local_var: VarRef,
+    max_length: usize,
+}
+
+impl ClauseSharing {
+    /// Publishes the clause to the other workers, unless it is too long or involves local variables.
+    pub fn export(&self, clause: &[Bound]) {
+        if clause.is_empty()
+            || clause.len() > self.max_length
+            || clause.iter().any(|l| l.variable() >= self.first_local_var)
+        {
+            return;
+        }
+        self.clauses.lock().unwrap().push((self.worker, clause.to_vec()));
+    }
+
+    /// Returns all clauses published by the other workers since the last import.
+    pub fn import(&mut self) -> Vec<Vec<Bound>> {
+        let clauses = self.clauses.lock().unwrap();
+        let imported = clauses[self.next_import..]
+            .iter()
+            .filter(|(origin, _)| *origin != self.worker)
+            .map(|(_, clause)| clause.clone())
+            .collect();
+        self.next_import = clauses.len();
+        imported
+    }
+}
+
+/// Search parameters of a single worker of a portfolio.
+#[derive(Clone, Debug)]
+pub struct WorkerParams {
+    pub branching: BranchingParams,
+}
+
+impl WorkerParams {
+    /// Parameters of the `i`-th worker of a portfolio. The first worker uses the default parameters,
+    /// the following ones vary the value preference, the restart policy and the use of LNS.
+    pub fn diversified(i: usize) -> WorkerParams {
+        let mut branching = BranchingParams::default();
+        if i % 2 == 1 {
+            branching.prefer_min_value = !branching.prefer_min_value;
+        }
+        if (i / 2) % 2 == 1 {
+            branching.lns = !branching.lns;
+        }
+        match (i / 4) % 3 {
+            1 => branching.increase_ratio_for_allowed_conflicts = 1.2,
+            2 => {
+                branching.allowed_conflicts *= 5;
+                branching.increase_ratio_for_allowed_conflicts = 2.0;
+            }
+            _ => (),
+        }
+        WorkerParams { branching }
+    }
+}
+
+/// Messages sent by the workers to the thread coordinating them.
+enum Message {
+    /// A new solution and its value for the objective (0 when not optimizing).
+    Solution(IntCst, SavedAssignment),
+    /// A new lower bound on the objective.
+    LowerBound(IntCst),
+    /// The worker has terminated. It is conclusive if it proved that no (better) solution exists.
+    Finished {
+        worker: usize,
+        conclusive: bool,
+        stats: Stats,
+    },
+}
+
+type TheoryBuilder<'a> = Box<dyn Fn(&mut Model) -> Box<dyn Theory> + Sync + 'a>;
+type BrancherBuilder<'a> = Box<dyn Fn(&BranchingParams) -> Box<dyn Brancher> + Sync + 'a>;
+type MonitorBuilder<'a> = Box<dyn Fn(usize) -> Box<dyn SearchMonitor> + Sync + 'a>;
+
+/// A portfolio of solvers, each running in its own thread on a copy of the same problem.
+pub struct ParSolver<'a> {
+    model: Model,
+    constraints: Vec<BAtom>,
+    theories: Vec<TheoryBuilder<'a>>,
+    /// Builds the brancher of each worker, using an [ActivityBrancher] if not set.
+    brancher: Option<BrancherBuilder<'a>>,
+    /// Builds the search monitors of each worker.
+    monitors: Vec<MonitorBuilder<'a>>,
+    /// Hinted values given to each worker and whether their variables should be prioritized, see [Solver::hint].
+    hint: Option<(Vec<(VarRef, IntCst)>, bool)>,
+    /// Assignment from which each worker starts its search, see [Solver::warm_start].
+    warm_start: Vec<(VarRef, IntCst)>,
+    workers: Vec<WorkerParams>,
+    limits: SearchLimits,
+    interrupt: Arc<AtomicBool>,
+    solution: Option<SavedAssignment>,
+    /// Statistics of each worker in the last call to `solve` or `minimize_with_strategy`.
+    pub stats: Vec<Stats>,
+}
+
+impl<'a> ParSolver<'a> {
+    /// Creates a portfolio of `num_workers` solvers for the given model and constraints.
+    /// Parameters of the workers are given by [WorkerParams::diversified].
+    pub fn new(model: Model, constraints: Vec<BAtom>, num_workers: usize) -> Self {
+        assert!(num_workers > 0, "A portfolio requires at least one worker");
+        ParSolver {
+            model,
+            constraints,
+            theories: Vec::new(),
+            brancher: None,
+            monitors: Vec::new(),
+            hint: None,
+            warm_start: Vec::new(),
+            workers: (0..num_workers).map(WorkerParams::diversified).collect(),
+            limits: SearchLimits::none(),
+            interrupt: Arc::new(AtomicBool::new(false)),
+            solution: None,
+            stats: Vec::new(),
+        }
+    }
+
+    /// Adds a theory to all workers. Since each worker has its own copy of the model, the theory is built
+    /// by invoking `build` on the copy of each worker.
+    pub fn add_theory(&mut self, build: impl Fn(&mut Model) -> Box<dyn Theory> + Sync + 'a) {
+        self.theories.push(Box::new(build));
+    }
+
+    /// Sets the brancher used by each worker, built by invoking `build` with the branching parameters of the worker.
+    pub fn set_brancher(&mut self, build: impl Fn(&BranchingParams) -> Box<dyn Brancher> + Sync + 'a) {
+        self.brancher = Some(Box::new(build));
+    }
+
+    /// Adds a search monitor to each worker, built by invoking `build` with the index of the worker.
+    pub fn add_monitor(&mut self, build: impl Fn(usize) -> Box<dyn SearchMonitor> + Sync + 'a) {
+        self.monitors.push(Box::new(build));
+    }
+
+    /// Sets a hint on the values of some variables that is given to each worker, see [Solver::hint].
+    pub fn hint(&mut self, values: Vec<(VarRef, IntCst)>, prioritize: bool) {
+        self.hint = Some((values, prioritize));
+    }
+
+    /// Sets a (possibly partial) assignment from which each worker starts its search, see [Solver::warm_start].
+    pub fn warm_start(&mut self, values: Vec<(VarRef, IntCst)>) {
+        self.warm_start = values;
+    }
+
+    /// Sets the search parameters of each worker, replacing the default ones.
+    /// The number of workers is the number of parameters provided.
+    pub fn set_worker_params(&mut self, params: Vec<WorkerParams>) {
+        assert!(!params.is_empty(), "A portfolio requires at least one worker");
+        self.workers = params;
+    }
+
+    pub fn num_workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Sets the limits on the search effort of each worker.
+    pub fn set_limits(&mut self, limits: SearchLimits) {
+        self.limits = limits;
+    }
+
+    /// Returns a flag that can be raised, possibly from another thread, to stop all workers.
+    pub fn interrupt_flag(&self) -> Arc<AtomicBool> {
+        self.interrupt.clone()
+    }
+
+    /// Solution found by the last call to `solve`, if any.
+    pub fn solution(&self) -> Option<&SavedAssignment> {
+        self.solution.as_ref()
+    }
+
+    /// Builds the solver of a worker from a copy of the model and constraints.
+    fn build_worker(&self, worker: usize, clauses: &SharedClauses) -> Solver {
+        let mut model = self.model.clone();
+        let theories: Vec<_> = self.theories.iter().map(|build| build(&mut model)).collect();
+        let params = &self.workers[worker].branching;
+        let brancher: Box<dyn Brancher> = match &self.brancher {
+            Some(build) => build(params),
+            None => Box::new(ActivityBrancher::with_params(params.clone())),
+        };
+        let mut solver = Solver::with_brancher(model, brancher);
+        for theory in theories {
+            solver.add_theory(theory);
+        }
+        for build in &self.monitors {
+            solver.add_monitor(build(worker));
+        }
+        solver.enforce_all(&self.constraints);
+        // set before the warm start, that searches for a solution extending its values
+        solver.set_limits(self.limits.clone());
+        solver.interrupt = self.interrupt.clone();
+        if let Some((values, prioritize)) = &self.hint {
+            solver.hint(values, *prioritize);
+        }
+        if !self.warm_start.is_empty() {
+            solver.warm_start(&self.warm_start);
+        }
+        // all workers build their model in the same way, so the variables they have in common are the ones created so far
+        solver.sharing = Some(ClauseSharing {
+            worker,
+            clauses: clauses.clone(),
+            next_import: 0,
+            first_local_var: VarRef::from(solver.model.discrete.variables().count()),
+            max_length: *MAX_SHARED_CLAUSE_LENGTH.get(),
+        });
+        solver
+    }
+
+    /// Runs `work` on each worker in its own thread and processes the messages they send in the current thread,
+    /// until all workers have terminated. All workers are stopped as soon as one of them reaches a conclusive
+    /// result or `on_message` returns true.
+    ///
+    /// Returns true if one of the workers reached a conclusive result.
+    fn run(
+        &mut self,
+        work: impl Fn(&mut Solver, &Sender<Message>) -> bool + Sync,
+        mut on_message: impl FnMut(Message) -> bool,
+    ) -> bool {
+        let clauses: SharedClauses = Default::default();
+        let (sender, receiver) = channel();
+        let mut stats: Vec<Stats> = (0..self.num_workers()).map(|_| Stats::default()).collect();
+        let mut conclusive = false;
+        let mut stop_requested = false;
+        // true if the interruption flag was raised by this method (and not externally)
+        let mut raised = false;
+        let this = &*self;
+        std::thread::scope(|scope| {
+            for worker in 0..this.num_workers() {
+                let sender = sender.clone();
+                let clauses = &clauses;
+                let work = &work;
+                scope.spawn(move || {
+                    let mut solver = this.build_worker(worker, clauses);
+                    let conclusive = work(&mut solver, &sender);
+                    let _ = sender.send(Message::Finished {
+                        worker,
+                        conclusive,
+                        stats: solver.stats,
+                    });
+                });
+            }
+            // only the workers hold a sender now, the loop ends once all of them are done
+            drop(sender);
+            for message in receiver {
+                match message {
+                    Message::Finished {
+                        worker,
+                        conclusive: c,
+                        stats: s,
+                    } => {
+                        stats[worker] = s;
+                        conclusive |= c;
+                    }
+                    message => {
+                        stop_requested |= on_message(message);
+                    }
+                }
+                if (conclusive || stop_requested) && !this.interrupt.load(Ordering::Relaxed) {
+                    // no need for the other workers to continue
+                    this.interrupt.store(true, Ordering::Relaxed);
+                    raised = true;
+                }
+            }
+        });
+        if raised {
+            // the interruption was only meant for this run
+            self.interrupt.store(false, Ordering::Relaxed);
+        }
+        self.stats = stats;
+        conclusive
+    }
+
+    /// Searches for a solution with all workers. If one is found, it is available through [ParSolver::solution].
+    pub fn solve(&mut self) -> SolveResult {
+        let mut solution = None;
+        let conclusive = self.run(
+            |solver, sender| match solver.solve() {
+                SolveResult::Sat => {
+                    let _ = sender.send(Message::Solution(0, solver.solution()));
+                    true
+                }
+                SolveResult::Unsat => true,
+                SolveResult::Unknown => false,
+            },
+            |message| {
+                if let Message::Solution(_, sol) = message {
+                    solution.get_or_insert(sol);
+                }
+                false
+            },
+        );
+        let result = match (&solution, conclusive) {
+            (Some(_), _) => SolveResult::Sat,
+            (None, true) => SolveResult::Unsat,
+            (None, false) => SolveResult::Unknown,
+        };
+        self.solution = solution;
+        result
+    }
+
+    /// Minimizes the objective with all workers, each using the given strategy.
+    ///
+    /// The `on_progress` callback is invoked (in the current thread) each time a worker improves the interval of
+    /// possible values of the optimum, see [Solver::minimize_with_strategy].
+    pub fn minimize_with_strategy(
+        &mut self,
+        objective: impl Into<IAtom>,
+        strategy: OptimizationStrategy,
+        mut on_progress: impl FnMut(ObjectiveBounds, Option<&SavedAssignment>),
+    ) -> OptimizationResult {
+        let objective = objective.into();
+        let (lb, ub) = self.model.domain_of(objective);
+        let mut bounds = ObjectiveBounds::new(lb, ub);
+        let mut best: Option<(IntCst, SavedAssignment)> = None;
+
+        let conclusive = self.run(
+            |solver, sender| {
+                let result = solver.minimize_with_strategy(objective, strategy, |bounds, sol| {
+                    let _ = match sol {
+                        Some(sol) => sender.send(Message::Solution(bounds.ub, sol.clone())),
+                        None => sender.send(Message::LowerBound(bounds.lb)),
+                    };
+                });
+                // a worker that found no solution might still have proven the optimality of a solution of another worker
+                matches!(
+                    result,
+                    OptimizationResult::Optimal { .. } | OptimizationResult::Infeasible
+                )
+            },
+            |message| {
+                match message {
+                    Message::Solution(value, sol) => {
+                        if !matches!(best, Some((best_value, _)) if best_value <= value) {
+                            bounds.ub = value;
+                            on_progress(bounds, Some(&sol));
+                            best = Some((value, sol));
+                        }
+                    }
+                    Message::LowerBound(lb) => {
+                        if lb > bounds.lb {
+                            bounds.lb = lb.min(bounds.ub);
+                            on_progress(bounds, None);
+                        }
+                    }
+                    Message::Finished { .. } => unreachable!(),
+                }
+                // bounds proven by different workers might meet, in which case all workers can be stopped
+                best.is_some() && bounds.lb >= bounds.ub
+            },
+        );
+        let optimal = conclusive || (best.is_some() && bounds.lb >= bounds.ub);
+        match best {
+            Some((value, solution)) if optimal => OptimizationResult::Optimal { value, solution },
+            Some((value, solution)) => OptimizationResult::Feasible {
+                value,
+                solution,
+                lower_bound: bounds.lb,
+            },
+            None if optimal => OptimizationResult::Infeasible,
+            None => OptimizationResult::Unknown { lower_bound: bounds.lb },
+        }
+    }
+}
@@ -0,0 +1,748 @@
+//! Simplification of a set of clauses, either before search or periodically at the root level (inprocessing).
+//!
+//! The [Preprocessor] implements the following techniques:
+//!  - forward and backward subsumption: removing clauses that are a superset of another clause,
+//!  - self-subsuming resolution: removing `!l` from a clause `D` if there is a clause `C` with `l` such that
+//!    `C \ {l}` is a subset of `D`,
+//!  - bounded variable elimination: replacing all clauses on a variable by their resolvents, when this
+//!    does not increase the number of clauses,
+//!  - failed-literal probing: a literal whose unit propagation leads to a conflict is set to false.
+//!
+//! Literals are treated as propositional atoms: two literals are only considered related if they are equal
+//! or the negation of each other. Eliminated variables have no value in a solution of the simplified problem:
+//! their value can be computed from the other variables with a [Reconstruction].
+
+use crate::solver::proof::ProofStep;
+use aries_backtrack::Backtrack;
+use aries_model::assignments::SavedAssignment;
+use aries_model::bounds::{Bound, Disjunction};
+use aries_model::lang::VarRef;
+use env_param::EnvParam;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Number of restarts between two inprocessing passes on the problem clauses. 0 disables inprocessing.
+pub static INPROCESSING_PERIOD: EnvParam<u64> = EnvParam::new("ARIES_SMT_INPROCESSING_PERIOD", "0");
+
+#[derive(Clone, Debug)]
+pub struct PreprocessingParams {
+    /// Enables subsumption and self-subsuming resolution.
+    pub subsumption: bool,
+    /// Enables bounded variable elimination.
+    pub variable_elimination: bool,
+    /// Enables failed-literal probing.
+    pub probing: bool,
+    /// Variables occurring in more than this number of clauses are not considered for elimination.
+    pub max_elimination_occurrences: usize,
+    /// A variable is not eliminated if this would result in a clause with more literals.
+    pub max_resolvent_length: usize,
+    /// Maximum number of clauses visited during failed-literal probing.
+    pub probing_budget: u64,
+}
+
+impl Default for PreprocessingParams {
+    fn default() -> Self {
+        PreprocessingParams {
+            subsumption: true,
+            variable_elimination: true,
+            probing: true,
+            max_elimination_occurrences: 16,
+            max_resolvent_length: 20,
+            probing_budget: 1_000_000,
+        }
+    }
+}
+
+impl PreprocessingParams {
+    /// Parameters for the simplification passes made at the root level during search.
+    /// Variable elimination is disabled as the eliminated variables might already appear in learnt clauses.
+    pub fn inprocessing() -> Self {
+        PreprocessingParams {
+            variable_elimination: false,
+            probing_budget: 100_000,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PreprocessingStats {
+    /// Number of clauses removed because they were subsumed by another one.
+    pub subsumed: u64,
+    /// Number of literals removed by self-subsuming resolution.
+    pub strengthened: u64,
+    /// Number of variables eliminated.
+    pub eliminated: u64,
+    /// Number of literals found to be false by probing.
+    pub failed_literals: u64,
+}
+
+impl PreprocessingStats {
+    pub fn add(&mut self, other: &PreprocessingStats) {
+        self.subsumed += other.subsumed;
+        self.strengthened += other.strengthened;
+        self.eliminated += other.eliminated;
+        self.failed_literals += other.failed_literals;
+    }
+}
+
+/// Stack of the clauses removed by variable elimination, allowing to restore the value of eliminated
+/// variables in a solution of the simplified problem.
+#[derive(Clone, Default)]
+pub struct Reconstruction {
+    /// Eliminated variables, in the order of their elimination. Each one is represented by a literal
+    /// on the variable, together with the clauses that were removed when eliminating it.
+    eliminated: Vec<(Bound, Vec<Vec<Bound>>)>,
+}
+
+impl Reconstruction {
+    pub fn is_empty(&self) -> bool {
+        self.eliminated.is_empty()
+    }
+
+    pub fn eliminated_variables(&self) -> impl Iterator<Item = VarRef> + '_ {
+        self.eliminated.iter().map(|(lit, _)| lit.variable())
+    }
+
+    /// Adds the variables eliminated in `other` after the ones already in this reconstruction stack.
+    pub fn extend(&mut self, other: Reconstruction) {
+        self.eliminated.extend(other.eliminated)
+    }
+
+    /// Returns a copy of `solution` in which each eliminated variable is given a value that satisfies all the
+    /// clauses removed when eliminating it.
+    ///
+    /// Variables are processed in the reverse order of their elimination: the clauses of a variable only involve
+    /// variables that were eliminated later (and thus already restored) or not at all.
+    pub fn restore(&self, solution: &SavedAssignment) -> SavedAssignment {
+        // for each eliminated variable, the literal on it that is true in the restored solution
+        let mut restored: HashMap<VarRef, Bound> = HashMap::with_capacity(self.eliminated.len());
+        let value = |lit: Bound, restored: &HashMap<VarRef, Bound>| match restored.get(&lit.variable()) {
+            Some(&true_lit) => Some(true_lit == lit),
+            None => solution.discrete.value(lit),
+        };
+        for (lit, clauses) in self.eliminated.iter().rev() {
+            let lit = *lit;
+            // the literal must be made true if a clause would be violated otherwise, otherwise we make it false
+            // which satisfies all clauses on its negation.
+            let required = clauses.iter().any(|clause| {
+                clause.contains(&lit) && clause.iter().all(|&l| l == lit || value(l, &restored) == Some(false))
+            });
+            let true_lit = if required { lit } else { !lit };
+            restored.insert(lit.variable(), true_lit);
+        }
+
+        // rebuild the solution from the root level, with all variables decided to their final value
+        let mut result = solution.clone();
+        result.reset();
+        result.save_state();
+        for var in solution.discrete.variables() {
+            let res = match restored.get(&var) {
+                Some(&true_lit) => result.discrete.decide(true_lit).map(|_| ()),
+                None => {
+                    let (lb, ub) = solution.discrete.domain_of(var);
+                    result
+                        .discrete
+                        .decide(Bound::geq(var, lb))
+                        .map(|_| ())
+                        .and_then(|_| result.discrete.decide(Bound::leq(var, ub)).map(|_| ()))
+                }
+            };
+            res.expect("Inconsistent domain when restoring eliminated variables");
+        }
+        result
+    }
+}
+
+/// Outcome of a subsumption check of a clause `D` by a clause `C`.
+enum Subsumption {
+    /// `C` is a subset of `D`, which can be removed.
+    Subsumes,
+    /// `C` is a subset of `D` once its literal `l` is replaced by `!l`: `!l` can be removed from `D`.
+    Strengthens(Bound),
+    None,
+}
+
+/// Checks whether the clause `c` subsumes (or self-subsumes) the clause `d`. Both must be sorted.
+fn subsumes(c: &[Bound], d: &[Bound]) -> Subsumption {
+    if c.len() > d.len() {
+        return Subsumption::None;
+    }
+    let mut flipped = None;
+    for &l in c {
+        if d.binary_search(&l).is_ok() {
+            continue;
+        }
+        if flipped.is_none() && d.binary_search(&!l).is_ok() {
+            flipped = Some(l);
+        } else {
+            return Subsumption::None;
+        }
+    }
+    match flipped {
+        None => Subsumption::Subsumes,
+        Some(l) => Subsumption::Strengthens(l),
+    }
+}
+
+/// A set of clauses being simplified. Clauses are added with [Preprocessor::add_clause] and the simplified ones
+/// are retrieved with [Preprocessor::clauses] after a call to [Preprocessor::run].
+pub struct Preprocessor {
+    params: PreprocessingParams,
+    /// All clauses, identified by their index. Removed clauses are replaced by `None`.
+    /// Literals of a clause are kept sorted.
+    clauses: Vec<Option<Vec<Bound>>>,
+    /// Clauses in which each literal appears. The lists are lazily updated: an entry might refer to a clause that
+    /// was removed or no longer contains the literal.
+    occurrences: HashMap<Bound, Vec<usize>>,
+    /// Literals that are true in all solutions.
+    units: HashSet<Bound>,
+    /// Units whose consequences have not been applied to the clauses yet.
+    pending_units: VecDeque<Bound>,
+    unsat: bool,
+    reconstruction: Reconstruction,
+    /// If set, records all clauses derived and removed, in an order in which each derived clause is implied
+    /// by the clauses present at this point (see [Preprocessor::record_proof]).
+    proof: Option<Vec<ProofStep>>,
+    pub stats: PreprocessingStats,
+}
+
+impl Preprocessor {
+    pub fn new(params: PreprocessingParams) -> Self {
+        Preprocessor {
+            params,
+            clauses: Vec::new(),
+            occurrences: HashMap::new(),
+            units: HashSet::new(),
+            pending_units: VecDeque::new(),
+            unsat: false,
+            reconstruction: Reconstruction::default(),
+            proof: None,
+            stats: PreprocessingStats::default(),
+        }
+    }
+
+    /// Starts recording the derivation of the simplified clauses, to be retrieved with [Preprocessor::take_proof].
+    /// The clauses given to [Preprocessor::add_clause] are assumed to be already present in the proof.
+    /// Once all the steps are applied, the proof contains exactly the simplified clauses.
+    pub fn record_proof(&mut self) {
+        self.proof = Some(Vec::new());
+    }
+
+    pub fn take_proof(&mut self) -> Vec<ProofStep> {
+        self.proof.take().unwrap_or_default()
+    }
+
+    fn log(&mut self, step: impl FnOnce() -> ProofStep) {
+        if let Some(proof) = &mut self.proof {
+            proof.push(step());
+        }
+    }
+
+    /// Returns true if the clauses were found to be unsatisfiable.
+    pub fn is_unsat(&self) -> bool {
+        self.unsat
+    }
+
+    fn value(&self, lit: Bound) -> Option<bool> {
+        if self.units.contains(&lit) {
+            Some(true)
+        } else if self.units.contains(&!lit) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    pub fn add_clause(&mut self, clause: impl Into<Vec<Bound>>) {
+        let original: Vec<Bound> = clause.into();
+        if original.iter().any(|&l| self.value(l) == Some(true)) {
+            self.log(|| ProofStep::Delete(original));
+            return;
+        }
+        let clause: Vec<Bound> = original.iter().copied().filter(|&l| self.value(l).is_none()).collect();
+        let clause = match Disjunction::new_non_tautological(clause) {
+            Some(clause) => Vec::from(clause),
+            None => {
+                self.log(|| ProofStep::Delete(original));
+                return;
+            }
+        };
+        if clause.len() < original.len() {
+            self.log(|| ProofStep::Add(clause.clone()));
+            self.log(|| ProofStep::Delete(original));
+        }
+        match clause.len() {
+            0 => self.unsat = true,
+            1 => self.add_unit(clause[0]),
+            _ => {
+                let id = self.clauses.len();
+                for &l in &clause {
+                    self.occurrences.entry(l).or_default().push(id);
+                }
+                self.clauses.push(Some(clause));
+            }
+        }
+    }
+
+    fn add_unit(&mut self, lit: Bound) {
+        match self.value(lit) {
+            Some(true) => {}
+            Some(false) => self.unsat = true,
+            None => {
+                self.units.insert(lit);
+                self.pending_units.push_back(lit);
+            }
+        }
+    }
+
+    /// Ids of the clauses that currently contain the literal.
+    fn occurrences_of(&self, lit: Bound) -> Vec<usize> {
+        match self.occurrences.get(&lit) {
+            Some(ids) => ids
+                .iter()
+                .copied()
+                .filter(|&id| matches!(&self.clauses[id], Some(cl) if cl.binary_search(&lit).is_ok()))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn remove_clause(&mut self, id: usize) {
+        if let Some(clause) = self.clauses[id].take() {
+            self.log(|| ProofStep::Delete(clause));
+        }
+    }
+
+    /// Removes a literal from a clause, possibly making it a unit clause.
+    /// The strengthened clause must be implied by the current clauses.
+    fn remove_literal(&mut self, id: usize, lit: Bound) {
+        let clause = self.clauses[id].as_mut().unwrap();
+        let index = clause.binary_search(&lit).unwrap();
+        if let Some(proof) = &mut self.proof {
+            let original = clause.clone();
+            clause.remove(index);
+            proof.push(ProofStep::Add(clause.clone()));
+            proof.push(ProofStep::Delete(original));
+        } else {
+            clause.remove(index);
+        }
+        if clause.len() == 1 {
+            // the unit is recorded in the set of units and the clause is no longer needed
+            let unit = clause[0];
+            self.clauses[id] = None;
+            self.add_unit(unit);
+        }
+    }
+
+    /// Simplifies the clauses with all pending units.
+    fn propagate_units(&mut self) {
+        while let Some(lit) = self.pending_units.pop_front() {
+            if self.unsat {
+                return;
+            }
+            for id in self.occurrences_of(lit) {
+                self.remove_clause(id);
+            }
+            for id in self.occurrences_of(!lit) {
+                self.remove_literal(id, !lit);
+            }
+        }
+    }
+
+    /// Runs all enabled simplifications.
+    /// Only the variables for which `eliminable` returns true are considered for elimination.
+    pub fn run(&mut self, eliminable: impl Fn(VarRef) -> bool) {
+        self.propagate_units();
+        if self.params.subsumption && !self.unsat {
+            self.subsumption();
+        }
+        if self.params.probing && !self.unsat {
+            self.probing();
+        }
+        if self.params.variable_elimination && !self.unsat {
+            self.variable_elimination(eliminable);
+            // resolvents might be subsumed by other clauses
+            if self.params.subsumption && !self.unsat {
+                self.subsumption();
+            }
+        }
+    }
+
+    /// Removes subsumed clauses and strengthens clauses through self-subsuming resolution.
+    fn subsumption(&mut self) {
+        let mut queue: Vec<usize> = (0..self.clauses.len())
+            .filter(|&id| self.clauses[id].is_some())
+            .collect();
+        // process the largest clauses last, so that they are popped first from the queue
+        queue.sort_by_key(|&id| std::cmp::Reverse(self.clauses[id].as_ref().unwrap().len()));
+        while let Some(c) = queue.pop() {
+            if self.unsat {
+                return;
+            }
+            self.propagate_units();
+            let clause = match &self.clauses[c] {
+                Some(clause) => clause.clone(),
+                None => continue,
+            };
+            // any clause subsumed by c contains its literal with the fewest occurrences, or its negation
+            let pivot = clause
+                .iter()
+                .copied()
+                .min_by_key(|&l| self.occurrences_of(l).len() + self.occurrences_of(!l).len())
+                .unwrap();
+            let mut candidates = self.occurrences_of(pivot);
+            candidates.extend(self.occurrences_of(!pivot));
+            for d in candidates {
+                if d == c {
+                    continue;
+                }
+                let other = match &self.clauses[d] {
+                    Some(other) => other,
+                    None => continue,
+                };
+                match subsumes(&clause, other) {
+                    Subsumption::Subsumes => {
+                        self.remove_clause(d);
+                        self.stats.subsumed += 1;
+                    }
+                    Subsumption::Strengthens(l) => {
+                        self.remove_literal(d, !l);
+                        self.stats.strengthened += 1;
+                        if self.clauses[d].is_some() {
+                            // the strengthened clause might now subsume other ones
+                            queue.push(d);
+                        }
+                    }
+                    Subsumption::None => {}
+                }
+            }
+        }
+        self.propagate_units();
+    }
+
+    /// Unit propagation of the literal over all clauses, without modifying them.
+    /// Returns true if it results in a conflict. Each visited clause consumes one unit of the budget
+    /// and false is returned if the budget is exhausted before reaching a conflict.
+    fn propagation_fails(&self, lit: Bound, budget: &mut u64) -> bool {
+        let mut assigned: HashSet<Bound> = HashSet::new();
+        let value = |l: Bound, assigned: &HashSet<Bound>| {
+            if assigned.contains(&l) {
+                Some(true)
+            } else if assigned.contains(&!l) {
+                Some(false)
+            } else {
+                None
+            }
+        };
+        let mut queue = vec![lit];
+        assigned.insert(lit);
+        while let Some(l) = queue.pop() {
+            for id in self.occurrences_of(!l) {
+                if *budget == 0 {
+                    return false;
+                }
+                *budget -= 1;
+                let clause = self.clauses[id].as_ref().unwrap();
+                let mut unassigned = None;
+                let mut num_unassigned = 0;
+                let mut satisfied = false;
+                for &other in clause {
+                    match value(other, &assigned) {
+                        Some(true) => {
+                            satisfied = true;
+                            break;
+                        }
+                        Some(false) => {}
+                        None => {
+                            num_unassigned += 1;
+                            unassigned = Some(other);
+                        }
+                    }
+                }
+                if satisfied {
+                    continue;
+                }
+                match num_unassigned {
+                    0 => return true,
+                    1 => {
+                        let implied = unassigned.unwrap();
+                        assigned.insert(implied);
+                        queue.push(implied);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        false
+    }
+
+    /// Sets to false all literals whose unit propagation results in a conflict.
+    fn probing(&mut self) {
+        let mut budget = self.params.probing_budget;
+        // only literals whose negation appears in a clause can trigger any propagation
+        let mut literals: Vec<Bound> = self
+            .occurrences
+            .keys()
+            .copied()
+            .filter(|&l| !self.occurrences_of(l).is_empty())
+            .map(|l| !l)
+            .collect();
+        literals.sort();
+        for lit in literals {
+            if budget == 0 || self.unsat {
+                break;
+            }
+            if self.value(lit).is_none() && self.propagation_fails(lit, &mut budget) {
+                self.stats.failed_literals += 1;
+                self.log(|| ProofStep::Add(vec![!lit]));
+                self.add_unit(!lit);
+                self.propagate_units();
+            }
+        }
+    }
+
+    /// Eliminates variables whose clauses can be replaced by a smaller set of resolvents.
+    fn variable_elimination(&mut self, eliminable: impl Fn(VarRef) -> bool) {
+        // literals on each variable, a variable is only eliminated if all its occurrences are on the same literal
+        let mut atoms: HashMap<VarRef, HashSet<Bound>> = HashMap::new();
+        for clause in self.clauses.iter().flatten() {
+            for &l in clause {
+                let atom = std::cmp::min(l, !l);
+                atoms.entry(l.variable()).or_default().insert(atom);
+            }
+        }
+        let mut candidates: Vec<(usize, Bound)> = atoms
+            .iter()
+            .filter(|(&var, atoms)| atoms.len() == 1 && eliminable(var))
+            .map(|(_, atoms)| {
+                let atom = *atoms.iter().next().unwrap();
+                (self.occurrences_of(atom).len() + self.occurrences_of(!atom).len(), atom)
+            })
+            .collect();
+        // try the variables with the fewest occurrences first
+        candidates.sort();
+
+        for (_, atom) in candidates {
+            self.propagate_units();
+            if self.unsat {
+                return;
+            }
+            if self.value(atom).is_some() {
+                continue;
+            }
+            let pos = self.occurrences_of(atom);
+            let neg = self.occurrences_of(!atom);
+            if pos.len() + neg.len() > self.params.max_elimination_occurrences {
+                continue;
+            }
+            let mut resolvents = Vec::new();
+            let mut too_large = false;
+            'outer: for &p in &pos {
+                for &n in &neg {
+                    let resolvent: Vec<Bound> = self.clauses[p]
+                        .as_ref()
+                        .unwrap()
+                        .iter()
+                        .chain(self.clauses[n].as_ref().unwrap().iter())
+                        .copied()
+                        .filter(|&l| l != atom && l != !atom)
+                        .collect();
+                    if let Some(resolvent) = Disjunction::new_non_tautological(resolvent) {
+                        let resolvent = Vec::from(resolvent);
+                        if resolvent.len() > self.params.max_resolvent_length
+                            || resolvents.len() >= pos.len() + neg.len()
+                        {
+                            too_large = true;
+                            break 'outer;
+                        }
+                        resolvents.push(resolvent);
+                    }
+                }
+            }
+            if too_large {
+                continue;
+            }
+            // eliminate the variable: replace all its clauses by their resolvents
+            // the resolvents are derived while their parents are still present
+            for resolvent in &resolvents {
+                self.log(|| ProofStep::Add(resolvent.clone()));
+            }
+            let mut removed = Vec::with_capacity(pos.len() + neg.len());
+            for id in pos.into_iter().chain(neg) {
+                let clause = self.clauses[id].take().unwrap();
+                self.log(|| ProofStep::Delete(clause.clone()));
+                removed.push(clause);
+            }
+            self.reconstruction.eliminated.push((atom, removed));
+            self.stats.eliminated += 1;
+            for resolvent in resolvents {
+                self.add_clause(resolvent);
+            }
+        }
+        self.propagate_units();
+    }
+
+    /// Returns the simplified clauses, including unit clauses.
+    pub fn clauses(&self) -> impl Iterator<Item = Vec<Bound>> + '_ {
+        let mut units: Vec<Bound> = self.units.iter().copied().collect();
+        units.sort();
+        units
+            .into_iter()
+            .map(|l| vec![l])
+            .chain(self.clauses.iter().flatten().cloned())
+    }
+
+    /// Returns the information needed to restore the value of the variables eliminated so far.
+    pub fn reconstruction(&self) -> &Reconstruction {
+        &self.reconstruction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aries_model::assignments::Assignment;
+    use aries_model::lang::BVar;
+    use aries_model::Model;
+
+    fn vars(model: &mut Model, n: usize) -> Vec<BVar> {
+        (0..n).map(|i| model.new_bvar(format!("x{}", i))).collect()
+    }
+
+    fn sorted(clauses: impl Iterator<Item = Vec<Bound>>) -> Vec<Vec<Bound>> {
+        let mut clauses: Vec<Vec<Bound>> = clauses.collect();
+        clauses.sort();
+        clauses
+    }
+
+    #[test]
+    fn test_subsumption() {
+        let model = &mut Model::new();
+        let x = vars(model, 4);
+        let mut pre = Preprocessor::new(PreprocessingParams {
+            variable_elimination: false,
+            probing: false,
+            ..Default::default()
+        });
+        // subsumed by the first clause
+        pre.add_clause(vec![x[0].true_lit(), x[1].true_lit()]);
+        pre.add_clause(vec![x[0].true_lit(), x[1].true_lit(), x[2].true_lit()]);
+        // strengthened to (x0 | x3) by the first clause
+        pre.add_clause(vec![x[0].true_lit(), x[1].false_lit(), x[3].true_lit()]);
+        pre.run(|_| false);
+        assert!(!pre.is_unsat());
+        assert_eq!(pre.stats.subsumed, 1);
+        assert_eq!(pre.stats.strengthened, 1);
+        let expected = sorted(
+            vec![
+                vec![x[0].true_lit(), x[1].true_lit()],
+                Vec::from(Disjunction::new(vec![x[0].true_lit(), x[3].true_lit()])),
+            ]
+            .into_iter(),
+        );
+        assert_eq!(sorted(pre.clauses()), expected);
+    }
+
+    #[test]
+    fn test_probing() {
+        let model = &mut Model::new();
+        let x = vars(model, 3);
+        let mut pre = Preprocessor::new(PreprocessingParams {
+            subsumption: false,
+            variable_elimination: false,
+            ..Default::default()
+        });
+        // x0 => x1, x0 => x2, x1 & x2 => false: x0 is a failed literal
+        pre.add_clause(vec![x[0].false_lit(), x[1].true_lit()]);
+        pre.add_clause(vec![x[0].false_lit(), x[2].true_lit()]);
+        pre.add_clause(vec![x[1].false_lit(), x[2].false_lit()]);
+        pre.run(|_| false);
+        assert!(!pre.is_unsat());
+        assert!(pre.stats.failed_literals >= 1);
+        assert!(pre.clauses().any(|cl| cl == vec![x[0].false_lit()]));
+    }
+
+    #[test]
+    fn test_elimination_and_reconstruction() {
+        let model = &mut Model::new();
+        let x = vars(model, 3);
+        let original = vec![
+            vec![x[0].true_lit(), x[1].true_lit()],
+            vec![x[0].false_lit(), x[2].true_lit()],
+            vec![x[1].false_lit(), x[2].false_lit()],
+        ];
+        let mut pre = Preprocessor::new(PreprocessingParams {
+            subsumption: false,
+            probing: false,
+            ..Default::default()
+        });
+        for clause in &original {
+            pre.add_clause(clause.clone());
+        }
+        // only x0 can be eliminated, its two clauses are replaced by (x1 | x2)
+        pre.run(|v| v == VarRef::from(x[0]));
+        assert_eq!(pre.stats.eliminated, 1);
+        let simplified = sorted(pre.clauses());
+        assert_eq!(simplified.len(), 2);
+        assert!(simplified
+            .iter()
+            .all(|cl| cl.iter().all(|l| l.variable() != x[0].into())));
+
+        // a solution of the simplified problem, with an arbitrary value for x0
+        model.save_state();
+        model.discrete.decide(x[0].false_lit()).unwrap();
+        model.discrete.decide(x[1].false_lit()).unwrap();
+        model.discrete.decide(x[2].true_lit()).unwrap();
+        let solution = pre.reconstruction().restore(&model.to_owned_assignment());
+        assert_eq!(solution.boolean_value_of(x[0]), Some(true));
+        assert_eq!(solution.boolean_value_of(x[1]), Some(false));
+        assert_eq!(solution.boolean_value_of(x[2]), Some(true));
+        for clause in &original {
+            assert!(clause.iter().any(|&l| solution.discrete.value(l) == Some(true)));
+        }
+    }
+
+    #[test]
+    fn test_proof_recording() {
+        let model = &mut Model::new();
+        let x = vars(model, 4);
+        let original = vec![
+            vec![x[0].true_lit(), x[1].true_lit()],
+            vec![x[0].true_lit(), x[1].false_lit(), x[2].true_lit()],
+            vec![x[0].false_lit(), x[3].true_lit()],
+            vec![x[2].false_lit(), x[3].false_lit()],
+            vec![x[1].true_lit(), x[2].true_lit(), x[3].true_lit()],
+        ];
+        let mut pre = Preprocessor::new(PreprocessingParams::default());
+        pre.record_proof();
+        for clause in &original {
+            pre.add_clause(clause.clone());
+        }
+        pre.run(|v| v == VarRef::from(x[0]));
+        assert!(pre.stats.strengthened + pre.stats.eliminated + pre.stats.failed_literals > 0);
+
+        // replaying the proof on the original clauses must result in the simplified clauses
+        let normalize = |clause: &Vec<Bound>| {
+            let mut clause = clause.clone();
+            clause.sort();
+            clause
+        };
+        let mut clauses: Vec<Vec<Bound>> = original.iter().map(normalize).collect();
+        for step in pre.take_proof() {
+            match step {
+                ProofStep::Add(clause) => clauses.push(normalize(&clause)),
+                ProofStep::Delete(clause) => {
+                    let clause = normalize(&clause);
+                    let index = clauses
+                        .iter()
+                        .position(|cl| cl == &clause)
+                        .expect("deleting an absent clause");
+                    clauses.remove(index);
+                }
+            }
+        }
+        clauses.sort();
+        clauses.dedup();
+        assert_eq!(clauses, sorted(pre.clauses()));
+    }
+}
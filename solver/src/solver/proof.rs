@@ -0,0 +1,328 @@
+//! Output of DRAT proofs, that allow certifying the unsatisfiability of boolean problems with an external
+//! checker such as `drat-trim`.
+//!
+//! A DRAT proof is a sequence of clause additions and deletions, in DIMACS format:
+//!  - `l1 l2 ... ln 0` adds a clause that must be implied by the current clauses (Reverse Unit Propagation or
+//!    Resolution Asymmetric Tautology),
+//!  - `d l1 l2 ... ln 0` deletes a clause.
+//!
+//! The proof of an unsatisfiable problem ends with the empty clause `0`.
+//!
+//! Only boolean variables (with a `[0, 1]` domain) can appear in a proof. Variables introduced by the solver
+//! (e.g. to reify a disjunction) are given fresh DIMACS indices: they are extension variables whose defining
+//! clauses are RAT on their first occurrence.
+
+use aries_model::bounds::{Bound, Relation};
+use aries_model::lang::VarRef;
+use std::collections::HashMap;
+use std::io::{BufWriter, Write};
+
+/// A step of a proof, as recorded by components (such as the preprocessor) that do not write the proof themselves.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProofStep {
+    /// Addition of a clause implied by the current ones.
+    Add(Vec<Bound>),
+    /// Deletion of a clause.
+    Delete(Vec<Bound>),
+}
+
+/// Writes a DRAT proof in the textual format.
+///
+/// IO errors do not interrupt the search: the first one is kept and reported by [DratWriter::finish].
+pub struct DratWriter {
+    output: BufWriter<Box<dyn Write + Send>>,
+    /// DIMACS index of each variable that appeared in the proof (or was explicitly named).
+    names: HashMap<VarRef, u32>,
+    /// Index of the next variable that is not explicitly named.
+    next_name: u32,
+    /// True if some variable was given a fresh name, after which no variable can be explicitly named.
+    fresh_names_used: bool,
+    error: Option<std::io::Error>,
+}
+
+impl DratWriter {
+    pub fn new(output: impl Write + Send + 'static) -> Self {
+        let output: Box<dyn Write + Send> = Box::new(output);
+        DratWriter {
+            output: BufWriter::new(output),
+            names: HashMap::new(),
+            next_name: 1,
+            fresh_names_used: false,
+            error: None,
+        }
+    }
+
+    /// Creates a writer for the proof in the given file, overwriting it if it already exists.
+    pub fn to_file(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        Ok(Self::new(std::fs::File::create(path)?))
+    }
+
+    /// Associates a variable to its index in the DIMACS file of the problem.
+    /// All variables of the original problem must be named before the first clause is written.
+    pub fn set_name(&mut self, var: VarRef, dimacs_index: u32) {
+        assert!(dimacs_index > 0, "DIMACS indices start at 1");
+        debug_assert!(
+            !self.fresh_names_used,
+            "Naming a variable after fresh names were attributed"
+        );
+        self.names.insert(var, dimacs_index);
+        self.next_name = self.next_name.max(dimacs_index + 1);
+    }
+
+    fn dimacs_literal(&mut self, lit: Bound) -> i64 {
+        debug_assert_eq!(lit.value(), 0, "Not a boolean literal: {:?}", lit);
+        let var = lit.variable();
+        let name = match self.names.get(&var) {
+            Some(&name) => name,
+            None => {
+                let name = self.next_name;
+                self.next_name += 1;
+                self.fresh_names_used = true;
+                self.names.insert(var, name);
+                name
+            }
+        } as i64;
+        match lit.relation() {
+            Relation::GT => name,
+            Relation::LEQ => -name,
+        }
+    }
+
+    fn write_clause(&mut self, prefix: &str, clause: impl IntoIterator<Item = Bound>) {
+        if self.error.is_some() {
+            return;
+        }
+        let mut line = String::from(prefix);
+        for lit in clause {
+            line.push_str(&self.dimacs_literal(lit).to_string());
+            line.push(' ');
+        }
+        line.push_str("0\n");
+        if let Err(e) = self.output.write_all(line.as_bytes()) {
+            self.error = Some(e);
+        }
+    }
+
+    /// Records the addition of a clause, that must be implied by the clauses currently in the proof.
+    pub fn add(&mut self, clause: impl IntoIterator<Item = Bound>) {
+        self.write_clause("", clause)
+    }
+
+    /// Records the deletion of a clause.
+    pub fn delete(&mut self, clause: impl IntoIterator<Item = Bound>) {
+        self.write_clause("d ", clause)
+    }
+
+    /// Records the derivation of the empty clause, which concludes the proof of unsatisfiability.
+    pub fn add_empty_clause(&mut self) {
+        self.add(std::iter::empty())
+    }
+
+    pub fn write_step(&mut self, step: &ProofStep) {
+        match step {
+            ProofStep::Add(clause) => self.add(clause.iter().copied()),
+            ProofStep::Delete(clause) => self.delete(clause.iter().copied()),
+        }
+    }
+
+    /// Flushes the proof and reports the first error that occurred while writing it, if any.
+    pub fn finish(mut self) -> std::io::Result<()> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.output.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::preprocessing::PreprocessingParams;
+    use crate::solver::{SolveResult, Solver};
+    use aries_model::lang::BAtom;
+    use aries_model::Model;
+    use std::collections::HashSet;
+    use std::sync::{Arc, Mutex};
+
+    /// An output whose content remains accessible after the writer is consumed.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_drat_output() {
+        let mut model = Model::new();
+        let a = model.new_bvar("a");
+        let b = model.new_bvar("b");
+        let c = model.new_bvar("c");
+
+        let buffer = SharedBuffer::default();
+        let mut proof = DratWriter::new(buffer.clone());
+        proof.set_name(a.into(), 2);
+        proof.set_name(b.into(), 1);
+        proof.add(vec![a.true_lit(), b.false_lit()]);
+        // c is not named and gets the first index after the named ones
+        proof.write_step(&ProofStep::Add(vec![c.false_lit()]));
+        proof.delete(vec![b.false_lit(), a.true_lit()]);
+        proof.add_empty_clause();
+        proof.finish().unwrap();
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(output, "2 -1 0\n-3 0\nd -1 2 0\n0\n");
+    }
+
+    /// True if unit propagation on the `clauses` leads to a conflict when all literals of the `clause` are false
+    /// (Reverse Unit Propagation).
+    fn is_rup(clauses: &[Vec<i64>], clause: &[i64]) -> bool {
+        let mut true_lits: HashSet<i64> = HashSet::new();
+        for &l in clause {
+            if true_lits.contains(&l) {
+                // tautology
+                return true;
+            }
+            true_lits.insert(-l);
+        }
+        loop {
+            let mut propagated = false;
+            for cl in clauses {
+                if cl.iter().any(|l| true_lits.contains(l)) {
+                    continue;
+                }
+                let unset: Vec<i64> = cl.iter().copied().filter(|l| !true_lits.contains(&-l)).collect();
+                match unset.len() {
+                    0 => return true,
+                    1 => {
+                        true_lits.insert(unset[0]);
+                        propagated = true;
+                    }
+                    _ => (),
+                }
+            }
+            if !propagated {
+                return false;
+            }
+        }
+    }
+
+    /// True if all resolvents of the `clause` on its first literal are RUP (Resolution Asymmetric Tautology).
+    fn is_rat(clauses: &[Vec<i64>], clause: &[i64]) -> bool {
+        let pivot = match clause.first() {
+            Some(&pivot) => pivot,
+            None => return false,
+        };
+        clauses.iter().filter(|cl| cl.contains(&-pivot)).all(|cl| {
+            let resolvent: Vec<i64> = clause
+                .iter()
+                .copied()
+                .chain(cl.iter().copied().filter(|&l| l != -pivot))
+                .collect();
+            is_rup(clauses, &resolvent)
+        })
+    }
+
+    /// Replays a DRAT `proof` of the unsatisfiability of the `cnf`, checking that each added clause is implied
+    /// by the current ones, that each deleted clause is present and that the proof ends with the empty clause.
+    /// Returns the number of deletions in the proof.
+    fn check_drat(cnf: &[Vec<i64>], proof: &str) -> usize {
+        let as_set = |clause: &[i64]| clause.iter().copied().collect::<HashSet<i64>>();
+        let mut clauses = cnf.to_vec();
+        let mut num_deletions = 0;
+        let mut refuted = false;
+        for line in proof.lines() {
+            assert!(!refuted, "Step after the empty clause: {}", line);
+            let (deletion, line) = match line.strip_prefix("d ") {
+                Some(line) => (true, line),
+                None => (false, line),
+            };
+            let mut clause: Vec<i64> = line.split_whitespace().map(|l| l.parse().unwrap()).collect();
+            assert_eq!(clause.pop(), Some(0));
+            if deletion {
+                let index = clauses
+                    .iter()
+                    .position(|cl| as_set(cl) == as_set(&clause))
+                    .unwrap_or_else(|| panic!("Deletion of a clause that is not present: {:?}", clause));
+                clauses.swap_remove(index);
+                num_deletions += 1;
+            } else {
+                assert!(
+                    is_rup(&clauses, &clause) || is_rat(&clauses, &clause),
+                    "Clause not implied: {:?}",
+                    clause
+                );
+                refuted = clause.is_empty();
+                clauses.push(clause);
+            }
+        }
+        assert!(refuted, "The proof does not end with the empty clause");
+        num_deletions
+    }
+
+    #[test]
+    fn test_proof_of_unsat() {
+        // pigeonhole problem with 3 pigeons and 2 holes, the variable of pigeon i being in hole j is 2i+j+1
+        let p = |i: i64, j: i64| 2 * i + j + 1;
+        let mut cnf = Vec::new();
+        for i in 0..3 {
+            cnf.push(vec![p(i, 0), p(i, 1)]);
+        }
+        for j in 0..2 {
+            for i in 0..3 {
+                for k in (i + 1)..3 {
+                    cnf.push(vec![-p(i, j), -p(k, j)]);
+                }
+            }
+        }
+        // 7 is true at the root: the clauses in which it appears are removed or strengthened by the root simplification
+        cnf.push(vec![7]);
+        cnf.push(vec![7, p(0, 0)]);
+        cnf.push(vec![-7, p(0, 0), p(0, 1)]);
+
+        for &preprocess in &[false, true] {
+            let mut model = Model::new();
+            let vars: Vec<_> = (1..=7).map(|i| model.new_bvar(i.to_string())).collect();
+            let constraints: Vec<BAtom> = cnf
+                .iter()
+                .map(|clause| {
+                    let lits: Vec<BAtom> = clause
+                        .iter()
+                        .map(|&l| {
+                            let var = vars[(l.abs() - 1) as usize];
+                            let lit = if l > 0 { var.true_lit() } else { var.false_lit() };
+                            lit.into()
+                        })
+                        .collect();
+                    model.or(&lits)
+                })
+                .collect();
+
+            let buffer = SharedBuffer::default();
+            let mut proof = DratWriter::new(buffer.clone());
+            for (i, &var) in vars.iter().enumerate() {
+                proof.set_name(var.into(), i as u32 + 1);
+            }
+            let mut solver = Solver::new(model);
+            solver.set_proof(proof);
+            solver.enforce_all(&constraints);
+            if preprocess {
+                solver.preprocess(&PreprocessingParams::default(), &[]);
+            }
+            assert_eq!(solver.solve(), SolveResult::Unsat);
+            solver.take_proof().unwrap().finish().unwrap();
+
+            let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+            let num_deletions = check_drat(&cnf, &output);
+            if !preprocess {
+                // removal of the satisfied clause and replacement of the strengthened one
+                assert!(num_deletions >= 2);
+                assert!(solver.reasoners.sat.stats().satisfied_clauses >= 1);
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use env_param::EnvParam;
+use std::collections::VecDeque;
+use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
+
+/// Restart policy of the default brancher.
+/// The value of this parameter is loaded from the environment variable `ARIES_SMT_RESTART_POLICY`.
+/// Possible values are `geometric` (default), `luby` and `glucose`.
+pub static RESTART_POLICY: EnvParam<RestartPolicy> = EnvParam::new("ARIES_SMT_RESTART_POLICY", "geometric");
+
+/// Number of learnt clauses considered as recent by the glucose restart policy.
+pub static GLUCOSE_WINDOW: EnvParam<usize> = EnvParam::new("ARIES_SMT_GLUCOSE_WINDOW", "50");
+
+/// The glucose restart policy restarts when the average LBD of recent learnt clauses, multiplied by this factor,
+/// exceeds the average LBD of all learnt clauses.
+pub static GLUCOSE_FACTOR: EnvParam<f32> = EnvParam::new("ARIES_SMT_GLUCOSE_FACTOR", "0.8");
+
+/// Policy that determines when the solver should restart.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RestartPolicy {
+    /// Restart after a number of conflicts that is multiplied by a constant ratio after each restart.
+    Geometric,
+    /// Restart after a number of conflicts given by the Luby sequence (1, 1, 2, 1, 1, 2, 4, ...),
+    /// multiplied by the initially allowed number of conflicts.
+    Luby,
+    /// Restart when recently learnt clauses have a high Literal Block Distance (LBD) compared to the average
+    /// one, indicating that the search is not making progress (as in the Glucose SAT solver).
+    Glucose,
+}
+
+impl FromStr for RestartPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "geometric" => Ok(RestartPolicy::Geometric),
+            "luby" => Ok(RestartPolicy::Luby),
+            "glucose" => Ok(RestartPolicy::Glucose),
+            _ => Err(format!("Unknown restart policy: {}", s)),
+        }
+    }
+}
+
+impl Display for RestartPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            RestartPolicy::Geometric => write!(f, "geometric"),
+            RestartPolicy::Luby => write!(f, "luby"),
+            RestartPolicy::Glucose => write!(f, "glucose"),
+        }
+    }
+}
+
+/// Returns the i-th element (starting at 1) of the Luby sequence: 1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8, ...
+pub fn luby(i: u64) -> u64 {
+    debug_assert!(i >= 1);
+    let mut i = i;
+    loop {
+        // smallest k such that i <= 2^k - 1
+        let mut k = 1;
+        while (1 << k) - 1 < i {
+            k += 1;
+        }
+        if i == (1 << k) - 1 {
+            return 1 << (k - 1);
+        }
+        // i is in the repeated prefix of the sequence
+        i -= (1 << (k - 1)) - 1;
+    }
+}
+
+/// Keeps track of the LBD of learnt clauses, both over a sliding window of recent ones and over the whole search.
+pub struct LbdAverages {
+    window: usize,
+    recent: VecDeque<u32>,
+    recent_sum: u64,
+    total_sum: u64,
+    total_count: u64,
+}
+
+impl LbdAverages {
+    pub fn new(window: usize) -> Self {
+        LbdAverages {
+            window,
+            recent: VecDeque::with_capacity(window),
+            recent_sum: 0,
+            total_sum: 0,
+            total_count: 0,
+        }
+    }
+
+    pub fn add(&mut self, lbd: u32) {
+        self.total_sum += lbd as u64;
+        self.total_count += 1;
+        self.recent.push_back(lbd);
+        self.recent_sum += lbd as u64;
+        if self.recent.len() > self.window {
+            self.recent_sum -= self.recent.pop_front().unwrap() as u64;
+        }
+    }
+
+    /// Forgets about the recent clauses, typically after a restart.
+    pub fn clear_recent(&mut self) {
+        self.recent.clear();
+        self.recent_sum = 0;
+    }
+
+    /// Returns true if the window of recent clauses is full and their average LBD, multiplied by `factor`,
+    /// is greater than the global average.
+    pub fn recent_above_average(&self, factor: f32) -> bool {
+        if self.recent.len() < self.window {
+            return false;
+        }
+        let recent_avg = self.recent_sum as f32 / self.recent.len() as f32;
+        let total_avg = self.total_sum as f32 / self.total_count as f32;
+        recent_avg * factor > total_avg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_luby() {
+        let seq: Vec<u64> = (1..=15).map(luby).collect();
+        assert_eq!(seq, vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+    }
+
+    #[test]
+    fn test_lbd_averages() {
+        let mut lbds = LbdAverages::new(2);
+        lbds.add(2);
+        assert!(!lbds.recent_above_average(0.8));
+        lbds.add(2);
+        assert!(!lbds.recent_above_average(0.8));
+        lbds.add(10);
+        lbds.add(10);
+        // recent: 10, total average: 6
+        assert!(lbds.recent_above_average(0.8));
+        lbds.clear_recent();
+        assert!(!lbds.recent_above_average(0.8));
+    }
+}
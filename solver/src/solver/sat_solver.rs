@@ -1,4 +1,6 @@
-use crate::clauses::{Clause, ClauseDB, ClauseId, ClausesParams};
+use crate::clauses::{Clause, ClauseDB, ClauseId, ClauseTier, ClausesParams, DbSize};
+use crate::solver::proof::DratWriter;
+use crate::solver::stats::StatValue;
 use crate::solver::{Binding, BindingResult, EnforceResult};
 use aries_backtrack::{Backtrack, DecLvl, ObsTrail, ObsTrailCursor, Trail};
 use aries_collections::set::RefSet;
@@ -76,6 +78,8 @@ struct SearchState {
     /// Number of conflicts (as given in stats) at which the last DB expansion was made.
     conflicts_at_last_db_expansion: u64,
     allowed_conflicts_before_db_expansion: u64,
+    /// Number of events in the model (all at the root level) when the database was last simplified.
+    root_events_at_last_simplify: u32,
 }
 
 impl Default for SearchState {
@@ -84,24 +88,84 @@ impl Default for SearchState {
             allowed_learnt: f64::NAN,
             conflicts_at_last_db_expansion: 0,
             allowed_conflicts_before_db_expansion: 100, // TODO: read from env and synchronize with restarts
+            root_events_at_last_simplify: 0,
         }
     }
 }
 
+/// Maximum number of samples kept in [Stats::db_size_history].
+const MAX_DB_SIZE_HISTORY: usize = 64;
+
 #[derive(Debug)]
 pub struct Stats {
     pub conflicts: u64,
     pub propagations: u64,
+    /// Number of reductions of the clause database.
+    pub db_reductions: u64,
+    /// Total number of learnt clauses removed from the database.
+    pub removed_clauses: u64,
+    /// Size of the clause database right after some of the reductions, together with the number of conflicts
+    /// at this time. The samples are evenly spaced over all reductions and there are at most 64 of them.
+    pub db_size_history: Vec<(u64, DbSize)>,
+    /// Number of reductions between two samples of `db_size_history`, doubled each time the history is full.
+    db_size_sampling_period: u64,
+    /// Number of simplifications of the database with respect to the root level.
+    pub simplifications: u64,
+    /// Number of clauses removed by root-level simplification, because they were satisfied.
+    pub satisfied_clauses: u64,
+    /// Number of literals removed from clauses by root-level simplification, because they were falsified.
+    pub false_literals: u64,
 }
 impl Default for Stats {
     fn default() -> Self {
         Stats {
             conflicts: 0,
             propagations: 0,
+            db_reductions: 0,
+            removed_clauses: 0,
+            db_size_history: Vec::new(),
+            db_size_sampling_period: 1,
+            simplifications: 0,
+            satisfied_clauses: 0,
+            false_literals: 0,
+        }
+    }
+}
+
+impl Stats {
+    /// Records the size of the database after a reduction, if the reduction is one of the sampled ones.
+    fn record_db_size(&mut self, size: DbSize) {
+        debug_assert!(self.db_reductions > 0);
+        if !(self.db_reductions - 1).is_multiple_of(self.db_size_sampling_period) {
+            return;
+        }
+        if self.db_size_history.len() >= MAX_DB_SIZE_HISTORY {
+            // only keep every other sample, the current reduction is still aligned with the doubled period
+            let mut index = 0;
+            self.db_size_history.retain(|_| {
+                index += 1;
+                index % 2 == 1
+            });
+            self.db_size_sampling_period *= 2;
         }
+        self.db_size_history.push((self.conflicts, size));
     }
 }
 
+/// Computes the Literal Block Distance (LBD) of a clause: the number of distinct non-root decision levels
+/// at which its falsified literals were set. Literals that are not falsified are ignored.
+pub fn lbd(clause: impl IntoIterator<Item = Bound>, model: &DiscreteModel) -> u32 {
+    let mut levels: Vec<DecLvl> = clause
+        .into_iter()
+        .filter_map(|lit| model.implying_event(!lit))
+        .map(|ev| model.trail().decision_level(ev))
+        .filter(|&dl| dl > DecLvl::ROOT)
+        .collect();
+    levels.sort_unstable();
+    levels.dedup();
+    levels.len() as u32
+}
+
 pub struct SatSolver {
     clauses: ClauseDB,
     watches: Watches<ClauseId>,
@@ -120,6 +184,8 @@ pub struct SatSolver {
     tautology: Bound,
     /// A working data structure to avoid allocations during propagation
     working_watches: WatchSet<ClauseId>,
+    /// If set, all additions and deletions of clauses are recorded in this DRAT proof.
+    proof: Option<DratWriter>,
 }
 impl SatSolver {
     pub fn new(token: WriterId, model: &mut Model) -> SatSolver {
@@ -136,9 +202,27 @@ impl SatSolver {
             stats: Default::default(),
             tautology: model.tautology,
             working_watches: Default::default(),
+            proof: None,
         }
     }
 
+    /// Starts recording all additions and deletions of clauses in a DRAT proof.
+    /// Must be set before any clause is added to the database.
+    pub fn set_proof(&mut self, mut proof: DratWriter) {
+        debug_assert_eq!(self.clauses.num_clauses(), 0);
+        // the tautology variable has no clause forcing it to be true
+        proof.add(std::iter::once(self.tautology));
+        self.proof = Some(proof);
+    }
+
+    pub fn proof_mut(&mut self) -> Option<&mut DratWriter> {
+        self.proof.as_mut()
+    }
+
+    pub fn take_proof(&mut self) -> Option<DratWriter> {
+        self.proof.take()
+    }
+
     /// Adds a new clause that will be part of the problem definition.
     /// Returns a unique and stable identifier for the clause.
     pub fn add_clause(&mut self, clause: impl Into<Disjunction>) -> ClauseId {
@@ -151,12 +235,201 @@ impl SatSolver {
         self.add_clause_impl(clause.into(), true);
     }
 
+    /// Adds a clause learnt from a conflict, whose Literal Block Distance (LBD) was computed when it was
+    /// still violated. The LBD determines how long the clause will be kept in the database.
+    pub fn add_learnt_clause(&mut self, clause: impl Into<Disjunction>, lbd: u32) {
+        let clause = clause.into();
+        if let Some(proof) = &mut self.proof {
+            proof.add(clause.literals().iter().copied());
+        }
+        let cl_id = self.clauses.add_learnt_clause(Clause::new(clause), lbd);
+        self.pending_clauses.push_back(cl_id);
+    }
+
+    /// Adds back a problem clause that was previously obtained from [SatSolver::take_problem_clauses],
+    /// possibly after simplification. The clause is assumed to be already part of the proof (if any).
+    pub fn restore_problem_clause(&mut self, clause: impl Into<Disjunction>) {
+        let cl_id = self.clauses.add_clause(Clause::new(clause.into()), false);
+        self.pending_clauses.push_back(cl_id);
+    }
+
     fn add_clause_impl(&mut self, clause: Disjunction, learnt: bool) -> ClauseId {
+        if let Some(proof) = &mut self.proof {
+            proof.add(clause.literals().iter().copied());
+        }
         let cl_id = self.clauses.add_clause(Clause::new(clause), learnt);
         self.pending_clauses.push_back(cl_id);
         cl_id
     }
 
+    /// Removes from the database all clauses of the problem definition (i.e. not learnt) and returns them,
+    /// simplified with respect to the current state of the model: clauses that are satisfied are dropped and
+    /// falsified literals are removed.
+    ///
+    /// Must be called at the root level, where clauses that are locked (because they propagated a literal)
+    /// are satisfied and kept in the database.
+    pub fn take_problem_clauses(&mut self, model: &DiscreteModel) -> Vec<Vec<Bound>> {
+        debug_assert_eq!(model.current_decision_level(), DecLvl::ROOT);
+        debug_assert!(self.pending_clauses.is_empty());
+        let ids: Vec<ClauseId> = self
+            .clauses
+            .all_clauses()
+            .filter(|&id| !self.clauses.is_learnt(id) && !self.locks.contains(id))
+            .collect();
+        let mut clauses = Vec::with_capacity(ids.len());
+        for id in ids {
+            let clause = self.detach_clause(id);
+            if clause.literals().any(|l| model.entails(l)) {
+                if let Some(proof) = &mut self.proof {
+                    proof.delete(clause.literals());
+                }
+                continue;
+            }
+            let simplified: Vec<Bound> = clause.literals().filter(|&l| !model.entails(!l)).collect();
+            if let Some(proof) = &mut self.proof {
+                // unchanged clauses are given back to the caller and thus remain in the proof
+                if simplified.len() < clause.len() {
+                    proof.add(simplified.iter().copied());
+                    proof.delete(clause.literals());
+                }
+            }
+            clauses.push(simplified);
+        }
+        clauses
+    }
+
+    /// Simplifies the clause database with respect to the literals that hold at the root level (as MiniSat's `simplify()`):
+    /// satisfied clauses are removed and falsified literals are stripped from the remaining ones.
+    ///
+    /// Must be called at the root level, once propagation has reached a fixed point. Nothing is done if no literal
+    /// was fixed since the previous simplification. Locked clauses are left untouched as they explain root literals.
+    pub fn simplify(&mut self, model: &DiscreteModel) {
+        debug_assert_eq!(model.current_decision_level(), DecLvl::ROOT);
+        debug_assert!(self.pending_clauses.is_empty());
+        if model.num_events() == self.state.root_events_at_last_simplify {
+            return;
+        }
+        self.state.root_events_at_last_simplify = model.num_events();
+        self.stats.simplifications += 1;
+
+        let ids: Vec<ClauseId> = self
+            .clauses
+            .all_clauses()
+            .filter(|&id| !self.locks.contains(id))
+            .collect();
+        for id in ids {
+            let clause = &self.clauses[id];
+            if clause.literals().any(|l| model.entails(l)) {
+                self.remove_clause(id);
+                self.stats.satisfied_clauses += 1;
+                continue;
+            }
+            if !clause.literals().any(|l| model.entails(!l)) {
+                continue;
+            }
+            let simplified: Vec<Bound> = clause.literals().filter(|&l| !model.entails(!l)).collect();
+            self.stats.false_literals += (clause.len() - simplified.len()) as u64;
+            if let Some(proof) = &mut self.proof {
+                proof.add(simplified.iter().copied());
+                proof.delete(clause.literals());
+            }
+            // replace the clause in place, to keep its identifier and metadata
+            self.watches.remove_watch(id, !clause.watch1);
+            if clause.len() >= 2 {
+                self.watches.remove_watch(id, !clause.watch2);
+            }
+            self.clauses[id] = Clause::new(Disjunction::new(simplified));
+            if self.clauses[id].len() >= 2 {
+                // all remaining literals are unset, any two of them can be watched
+                self.set_watch_on_first_literals(id);
+            } else {
+                // not expected after a complete propagation, let the clause be processed as a new one
+                self.pending_clauses.push_back(id);
+            }
+        }
+    }
+
+    /// Removes all learnt clauses that are not locked and contain a literal on which `pred` holds.
+    pub fn remove_learnt_clauses_with(&mut self, pred: impl Fn(Bound) -> bool) {
+        let ids: Vec<ClauseId> = self
+            .clauses
+            .all_clauses()
+            .filter(|&id| {
+                self.clauses.is_learnt(id) && !self.locks.contains(id) && self.clauses[id].literals().any(&pred)
+            })
+            .collect();
+        for id in ids {
+            self.remove_clause(id);
+        }
+    }
+
+    fn remove_clause(&mut self, id: ClauseId) {
+        let removed = self.detach_clause(id);
+        if let Some(proof) = &mut self.proof {
+            proof.delete(removed.literals());
+        }
+    }
+
+    /// Removes a clause from the database, without recording its deletion in the proof.
+    fn detach_clause(&mut self, id: ClauseId) -> Clause {
+        let watches = &mut self.watches;
+        self.clauses
+            .remove_clause(id, &mut |clause, watched| watches.remove_watch(clause, watched))
+    }
+
+    /// Returns the current size of the clause database.
+    pub fn db_size(&self) -> DbSize {
+        self.clauses.size()
+    }
+
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Counters of the SAT solver, as (key, value) pairs.
+    pub fn stats_entries(&self) -> Vec<(String, StatValue)> {
+        let size = self.db_size();
+        vec![
+            ("fixed_clauses".to_string(), size.fixed.into()),
+            ("learnt_clauses".to_string(), size.num_learnt().into()),
+            ("db_reductions".to_string(), self.stats.db_reductions.into()),
+            ("removed_clauses".to_string(), self.stats.removed_clauses.into()),
+            ("simplifications".to_string(), self.stats.simplifications.into()),
+            ("satisfied_clauses".to_string(), self.stats.satisfied_clauses.into()),
+            ("false_literals".to_string(), self.stats.false_literals.into()),
+        ]
+    }
+
+    pub fn print_stats(&self) {
+        println!("# clauses: {}", self.db_size());
+        println!("# DB reductions: {}", self.stats.db_reductions);
+        println!("# removed clauses: {}", self.stats.removed_clauses);
+        println!(
+            "# simplifications: {} ({} satisfied clauses, {} false literals removed)",
+            self.stats.simplifications, self.stats.satisfied_clauses, self.stats.false_literals
+        );
+        // evolution of the DB size, limited to ten evenly spaced samples
+        let history = &self.stats.db_size_history;
+        let step = history.len().div_ceil(10);
+        for (conflicts, size) in history.iter().step_by(step.max(1)) {
+            println!("   after {:>10} conflicts: {}", conflicts, size);
+        }
+    }
+
+    /// Marks a clause as used in a conflict or explanation. For learnt clauses outside of the core tier,
+    /// the LBD is recomputed, possibly promoting the clause to a better tier.
+    /// All literals of the clause, except the one being explained (if any), must be falsified in the model.
+    fn clause_used(&mut self, clause: ClauseId, model: &DiscreteModel) {
+        self.clauses.bump_activity(clause);
+        match self.clauses.learnt_tier(clause) {
+            Some(ClauseTier::Tier2) | Some(ClauseTier::Local) => {
+                let lbd = lbd(self.clauses[clause].literals(), model);
+                self.clauses.update_lbd(clause, lbd);
+            }
+            _ => {}
+        }
+    }
+
     /// Process a newly added clause, making no assumption on the status of the clause.
     ///
     /// The only requirement is that the clause should not have been processed yet.
@@ -261,7 +534,7 @@ impl SatSolver {
                     explanation.push(!b);
                 }
                 // bump the activity of the clause
-                self.clauses.bump_activity(violated);
+                self.clause_used(violated, model);
                 Err(explanation)
             }
         }
@@ -422,18 +695,19 @@ impl SatSolver {
         true
     }
 
+    /// Explains the inference of `literal` by the clause identified by `cause`.
+    ///
+    /// The explained literal may already be set in the model (e.g. when the explanation is requested
+    /// for minimizing a learnt clause), but all other literals of the clause must be falsified.
     pub fn explain(&mut self, literal: Bound, cause: u32, model: &DiscreteModel, explanation: &mut Explanation) {
-        debug_assert_eq!(model.value(literal), None);
+        debug_assert_ne!(model.value(literal), Some(false));
         let clause = ClauseId::from(cause);
         // bump the activity of any clause use in an explanation
-        self.clauses.bump_activity(clause);
+        self.clause_used(clause, model);
         let clause = &self.clauses[clause];
-        debug_assert!(model.unit_clause(clause));
         explanation.reserve(clause.len() - 1);
         for l in clause {
-            if l.entails(literal) {
-                debug_assert_eq!(model.value(l), None)
-            } else {
+            if !l.entails(literal) {
                 debug_assert_eq!(model.value(l), Some(false));
                 explanation.push(!l);
             }
@@ -445,16 +719,16 @@ impl SatSolver {
     /// If all slots are taken, this function can:
     ///  - expand the database with more slots. This occurs if a certain number of conflicts occurred
     ///    since the last expansion.
-    ///  - Remove learnt clauses from the DB. This typically removes about half the clauses, making
-    ///    sure that clauses that are used to explain the current value of the literal at kept.
-    ///    Clauses to be removed are the least active ones.
+    ///  - Remove learnt clauses from the DB, making sure that clauses that are used to explain the current
+    ///    value of the literal at kept. Clauses with a low LBD are kept, and about half of the other ones
+    ///    (the least active) are removed (see `ClauseDB::reduce_db`).
     fn scale_database(&mut self) {
         if self.state.allowed_learnt.is_nan() {
             let initial_clauses = self.clauses.num_clauses() - self.clauses.num_learnt();
             self.state.allowed_learnt =
                 self.params.init_learnt_base + initial_clauses as f64 * self.params.init_learnt_ratio;
         }
-        if self.clauses.num_learnt() as i64 - self.locks.num_locks() as i64 >= self.state.allowed_learnt as i64 {
+        if self.clauses.num_removable() as i64 - self.locks.num_locks() as i64 >= self.state.allowed_learnt as i64 {
             // we exceed the number of learnt clause in the DB.
             // Check if it is time to increase the DB maximum size, otherwise shrink it.
             if self.stats.conflicts - self.state.conflicts_at_last_db_expansion
@@ -476,7 +750,15 @@ impl SatSolver {
                 let mut remove_watch = |clause: ClauseId, watched: Bound| {
                     watches.remove_watch(clause, watched);
                 };
-                self.clauses.reduce_db(|cl| locks.contains(cl), &mut remove_watch);
+                let removed = self.clauses.reduce_db(|cl| locks.contains(cl), &mut remove_watch);
+                if let Some(proof) = &mut self.proof {
+                    for clause in &removed {
+                        proof.delete(clause.literals());
+                    }
+                }
+                self.stats.db_reductions += 1;
+                self.stats.removed_clauses += removed.len() as u64;
+                self.stats.record_db_size(self.clauses.size());
             }
         }
     }
@@ -513,6 +795,29 @@ impl SatSolver {
                 }
                 BindingResult::Refined
             }
+            Fun::Xor => {
+                assert_eq!(e.args.len(), 2, "XOR expects two arguments");
+                let mut lits = [reif; 2];
+                for (i, &a) in e.args.iter().enumerate() {
+                    let a = BAtom::try_from(a).expect("not a boolean");
+                    lits[i] = self.reify(a, model);
+                    bindings.push(Binding::new(lits[i], a));
+                }
+                let [a, b] = lits;
+                // reif <=> (a xor b)
+                let clauses = [
+                    vec![!reif, a, b],
+                    vec![!reif, !a, !b],
+                    vec![reif, !a, b],
+                    vec![reif, a, !b],
+                ];
+                for clause in clauses.iter() {
+                    if let Some(clause) = Disjunction::new_non_tautological(clause.clone()) {
+                        self.add_clause(clause);
+                    }
+                }
+                BindingResult::Refined
+            }
             _ => BindingResult::Unsupported,
         }
     }
@@ -817,6 +1122,47 @@ mod tests {
         assert!(sat.propagate(&mut model.discrete).is_err());
     }
 
+    #[test]
+    fn test_root_simplification() {
+        let writer = WriterId::new(1u8);
+        let model = &mut Model::new();
+        let a = model.new_bvar("a");
+        let b = model.new_bvar("b");
+        let c = model.new_bvar("c");
+        let d = model.new_bvar("d");
+
+        let mut sat = SatSolver::new(writer, model);
+        sat.add_clause(vec![a.true_lit(), b.true_lit(), c.true_lit()]);
+        sat.add_clause(vec![a.false_lit(), d.true_lit()]);
+        sat.add_clause(vec![b.true_lit(), c.true_lit(), d.false_lit()]);
+        sat.propagate(&mut model.discrete).unwrap();
+        sat.simplify(&model.discrete);
+        // nothing holds at the root yet
+        assert_eq!(sat.stats().simplifications, 0);
+        assert_eq!(sat.db_size().fixed, 3);
+
+        sat.add_clause(vec![a.true_lit()]);
+        sat.propagate(&mut model.discrete).unwrap();
+        assert_eq!(model.boolean_value_of(d), Some(true));
+        sat.simplify(&model.discrete);
+        // (a or b or c) is satisfied, (b or c or !d) is reduced to (b or c),
+        // the unit clause (a) and (!a or d) are kept as they explain the value of a and d
+        assert_eq!(sat.stats().satisfied_clauses, 1);
+        assert_eq!(sat.stats().false_literals, 1);
+        assert_eq!(sat.db_size().fixed, 3);
+
+        // a second simplification without any new root literal is a no-op
+        sat.simplify(&model.discrete);
+        assert_eq!(sat.stats().simplifications, 1);
+
+        // the reduced clause is still correctly watched
+        model.save_state();
+        sat.save_state();
+        model.discrete.decide(b.false_lit()).unwrap();
+        sat.propagate(&mut model.discrete).unwrap();
+        assert_eq!(model.boolean_value_of(c), Some(true));
+    }
+
     #[test]
     fn test_int_propagation() {
         let writer = WriterId::new(1u8);
@@ -1,6 +1,10 @@
 use std::fmt::{Display, Error, Formatter};
 
 use crate::cpu_time::*;
+use crate::solver::preprocessing::PreprocessingStats;
+use crate::solver::restarts::RestartPolicy;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_derive::Serialize;
 use std::time::Duration;
 
 /// Statistics of the solver. All times are in seconds.
@@ -13,11 +17,19 @@ pub struct Stats {
     pub num_decisions: u64,
     pub num_conflicts: u64,
     pub num_restarts: u64,
+    /// Number of conflicts after which the solver backtracked chronologically instead of backjumping.
+    pub num_chrono_backtracks: u64,
+    /// Number of literals removed from learnt clauses by minimization.
+    pub num_minimized_literals: u64,
+    /// Restart policy of the brancher, if it reported one.
+    pub restart_policy: Option<RestartPolicy>,
     pub propagation_time: CycleCount,
     // First module is sat solver, other are the theories
     pub per_module_propagation_time: Vec<CycleCount>,
     pub per_module_conflicts: Vec<u64>,
     pub per_module_propagation_loops: Vec<u64>,
+    /// Simplifications made by preprocessing and inprocessing.
+    pub preprocessing: PreprocessingStats,
 }
 
 impl Stats {
@@ -30,14 +42,59 @@ impl Stats {
             num_decisions: 0,
             num_conflicts: 0,
             num_restarts: 0,
+            num_chrono_backtracks: 0,
+            num_minimized_literals: 0,
+            restart_policy: None,
             propagation_time: CycleCount::zero(),
             per_module_propagation_time: vec![CycleCount::zero()],
             per_module_conflicts: vec![0],
             per_module_propagation_loops: vec![0],
+            preprocessing: PreprocessingStats::default(),
         }
     }
 }
 
+impl Stats {
+    /// Returns the statistics as a flat record. Times are in seconds and per-module counters are prefixed
+    /// with the name of the module (`sat` for the SAT solver, `theory1`, `theory2`, ... for the theories).
+    pub fn record(&self) -> StatsRecord {
+        let mut record = StatsRecord::new();
+        record.push("restarts", self.num_restarts);
+        if let Some(policy) = self.restart_policy {
+            record.push("restart_policy", policy.to_string());
+        }
+        record.push("decisions", self.num_decisions);
+        record.push("conflicts", self.num_conflicts);
+        record.push("chrono_backtracks", self.num_chrono_backtracks);
+        record.push("minimized_literals", self.num_minimized_literals);
+        let pre = &self.preprocessing;
+        record.push("preprocessing.subsumed", pre.subsumed);
+        record.push("preprocessing.strengthened", pre.strengthened);
+        record.push("preprocessing.eliminated", pre.eliminated);
+        record.push("preprocessing.failed_literals", pre.failed_literals);
+        for i in 0..self.per_module_propagation_loops.len() {
+            let module = module_name(i);
+            record.push(
+                format!("{}.propagation_loops", module),
+                self.per_module_propagation_loops[i],
+            );
+            record.push(format!("{}.conflicts", module), self.per_module_conflicts[i]);
+        }
+        record.push("init_time", self.init_time.as_secs_f64());
+        record.push("solve_time", self.solve_time.as_secs_f64());
+        record
+    }
+}
+
+/// Name of the i-th module of the solver in a [StatsRecord]: the SAT solver comes first, followed by the theories.
+pub(crate) fn module_name(i: usize) -> String {
+    if i == 0 {
+        "sat".to_string()
+    } else {
+        format!("theory{}", i)
+    }
+}
+
 impl Default for Stats {
     fn default() -> Self {
         Self::new()
@@ -57,7 +114,10 @@ impl Display for Stats {
         }
 
         label(f, "restarts")?;
-        writeln!(f, "{:<12}", self.num_restarts)?;
+        match self.restart_policy {
+            Some(policy) => writeln!(f, "{:<12} ({})", self.num_restarts, policy)?,
+            None => writeln!(f, "{:<12}", self.num_restarts)?,
+        }
 
         label(f, "decisions")?;
         val_throughput(f, self.num_decisions, &self.solve_time)?;
@@ -67,6 +127,22 @@ impl Display for Stats {
         val_throughput(f, self.num_conflicts, &self.solve_time)?;
         new_line(f)?;
 
+        if self.num_chrono_backtracks > 0 {
+            label(f, "chrono backtracks")?;
+            writeln!(f, "{}", self.num_chrono_backtracks)?;
+        }
+
+        label(f, "minimized literals")?;
+        writeln!(f, "{}", self.num_minimized_literals)?;
+
+        let pre = &self.preprocessing;
+        label(f, "preprocessing")?;
+        writeln!(
+            f,
+            "{} subsumed, {} strengthened, {} eliminated, {} failed literals",
+            pre.subsumed, pre.strengthened, pre.eliminated, pre.failed_literals
+        )?;
+
         writeln!(f, "================= ")?;
         label(f, "Solvers")?;
         write!(f, "{:>15}", "SAT")?;
@@ -104,3 +180,184 @@ impl Display for Stats {
         Ok(())
     }
 }
+
+/// Value of a single statistic.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum StatValue {
+    Int(u64),
+    Float(f64),
+    Text(String),
+}
+
+impl From<u64> for StatValue {
+    fn from(value: u64) -> Self {
+        StatValue::Int(value)
+    }
+}
+impl From<u32> for StatValue {
+    fn from(value: u32) -> Self {
+        StatValue::Int(value as u64)
+    }
+}
+impl From<usize> for StatValue {
+    fn from(value: usize) -> Self {
+        StatValue::Int(value as u64)
+    }
+}
+impl From<f64> for StatValue {
+    fn from(value: f64) -> Self {
+        StatValue::Float(value)
+    }
+}
+impl From<String> for StatValue {
+    fn from(value: String) -> Self {
+        StatValue::Text(value)
+    }
+}
+impl From<&str> for StatValue {
+    fn from(value: &str) -> Self {
+        StatValue::Text(value.to_string())
+    }
+}
+
+impl Display for StatValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            StatValue::Int(i) => write!(f, "{}", i),
+            StatValue::Float(x) => write!(f, "{}", x),
+            StatValue::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// An ordered list of named statistics, meant to be exported in a machine-readable format (JSON or CSV)
+/// to be processed by external tools.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StatsRecord {
+    entries: Vec<(String, StatValue)>,
+}
+
+impl StatsRecord {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a statistic at the end of the record.
+    pub fn push(&mut self, key: impl Into<String>, value: impl Into<StatValue>) {
+        self.entries.push((key.into(), value.into()));
+    }
+
+    /// Appends all given statistics, with their key prefixed by `prefix` and a dot.
+    pub fn extend_prefixed(&mut self, prefix: &str, entries: impl IntoIterator<Item = (String, StatValue)>) {
+        for (key, value) in entries {
+            self.entries.push((format!("{}.{}", prefix, key), value));
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&StatValue> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn entries(&self) -> &[(String, StatValue)] {
+        &self.entries
+    }
+
+    /// A JSON object with one field per statistic, in the order of the record.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Serialization of statistics cannot fail")
+    }
+
+    /// The comma-separated keys of the record, to be used as the header of a CSV file.
+    pub fn csv_header(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(k, _)| csv_field(k))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// The comma-separated values of the record, in the same order as in the [csv_header](Self::csv_header).
+    pub fn csv_row(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(_, v)| csv_field(&v.to_string()))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Writes the records to a file, one per solver (e.g. per worker of a parallel solver).
+/// The file is in CSV (a header followed by one row per record) if its extension is `csv`,
+/// and otherwise in JSON (an array of objects).
+pub fn write_stats_file(records: &[StatsRecord], path: &std::path::Path) -> std::io::Result<()> {
+    let content = if path.extension().is_some_and(|ext| ext == "csv") {
+        let mut csv = String::new();
+        if let Some(first) = records.first() {
+            csv.push_str(&first.csv_header());
+            csv.push('\n');
+        }
+        for record in records {
+            csv.push_str(&record.csv_row());
+            csv.push('\n');
+        }
+        csv
+    } else {
+        serde_json::to_string_pretty(records).expect("Serialization of statistics cannot fail")
+    };
+    std::fs::write(path, content)
+}
+
+impl Serialize for StatsRecord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.entries.len()))?;
+        for (key, value) in &self.entries {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_export() {
+        let mut stats = Stats::new();
+        stats.num_decisions = 12;
+        stats.num_conflicts = 3;
+        stats.per_module_conflicts.push(2);
+        stats.per_module_propagation_loops.push(5);
+        stats.solve_time = Duration::from_millis(1500);
+
+        let mut record = stats.record();
+        record.extend_prefixed("theory1", vec![("note".to_string(), StatValue::from("a, \"b\""))]);
+        assert_eq!(record.get("decisions"), Some(&StatValue::Int(12)));
+        assert_eq!(record.get("theory1.propagation_loops"), Some(&StatValue::Int(5)));
+        assert_eq!(record.get("solve_time"), Some(&StatValue::Float(1.5)));
+        assert_eq!(record.get("restart_policy"), None);
+
+        let json: serde_json::Value = serde_json::from_str(&record.to_json()).unwrap();
+        assert_eq!(json["conflicts"], 3);
+        assert_eq!(json["theory1.conflicts"], 2);
+        assert_eq!(json["solve_time"], 1.5);
+        assert_eq!(json["theory1.note"], "a, \"b\"");
+
+        let header = record.csv_header();
+        let row = record.csv_row();
+        assert!(header.starts_with("restarts,decisions,conflicts,chrono_backtracks,"));
+        assert!(row.starts_with("0,12,3,0,"));
+        assert!(row.ends_with(",\"a, \"\"b\"\"\""));
+        assert_eq!(header.split(',').count(), record.entries().len());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::solver::stats::StatValue;
 use crate::solver::{Binding, BindingResult};
 use crate::{Contradiction, Theory};
 use aries_backtrack::ObsTrail;
@@ -31,6 +32,10 @@ impl TheorySolver {
         self.theory.propagate(model)
     }
 
+    pub fn stats(&self) -> Vec<(String, StatValue)> {
+        self.theory.stats()
+    }
+
     pub fn print_stats(&self) {
         self.theory.print_stats()
     }
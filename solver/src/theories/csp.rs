@@ -1,189 +1,353 @@
-#![allow(warnings, unused)]
+//! Theory for global constraints (e.g. [MaxConstraint](max::MaxConstraint) or [AllDifferent](all_different::AllDifferent)), each implemented by a dedicated propagator.
+//!
+//! Each constraint is associated to a trigger literal and must hold whenever the trigger is true.
+//! Propagators filter the bounds of their variables and must be able to explain each of their inferences.
 
-use std::collections::HashMap;
+use std::convert::TryFrom;
 
-use aries_backtrack::Trail;
+use crate::solver::stats::StatValue;
+use crate::solver::{Binding, BindingResult};
+use crate::theories::scheduler::Scheduler;
+use crate::{Contradiction, Theory};
+use aries_backtrack::{Backtrack, DecLvl, ObsTrail};
 use aries_collections::ref_store::RefVec;
 use aries_collections::*;
 use aries_model::bounds::Bound;
-use aries_model::lang::{IVar, IntCst, VarRef};
-use aries_model::WModel;
+use aries_model::expressions::ExprHandle;
+use aries_model::int_model::{Cause, DiscreteModel, EmptyDomain, Explanation};
+use aries_model::lang::{Fun, IAtom, IVar, IntCst, VarRef};
+use aries_model::{Model, WriterId};
 
+pub mod all_different;
+pub mod element;
+// work in progress: lazy clause learning, not used by the theory yet
+#[allow(warnings, unused)]
 pub mod learn;
 pub mod max;
+#[allow(warnings, unused)]
 pub mod range_set;
+#[allow(warnings, unused)]
 pub mod signed_literal;
+pub mod table;
 
 create_ref_type!(CId);
 
-pub enum Change {
-    Lb(IVar),
-    Ub(IVar),
-}
-
-#[derive(Default)]
-struct VarMeta {
-    watches: Vec<CId>,
-}
+pub type Update = std::result::Result<(), Inconsistency>;
 
+/// Reason for which a propagator failed.
 #[derive(Debug)]
-pub enum UpdateFail {
-    EmptyDom(IVar),
+pub enum Inconsistency {
+    /// The domain of the variable became empty.
+    EmptyDomain(VarRef),
+    /// The bound of a constant atom could not be met: the constraint is violated in the current domains.
+    Violated,
 }
-pub type Update = std::result::Result<(), UpdateFail>;
 
-enum Event {
-    AddedWatch(IVar),
-    // removed a watch of the variable by the constraint.
-    // the watch was positioned at the given index
-    RemovedWatch(IVar, CId, usize),
+impl From<EmptyDomain> for Inconsistency {
+    fn from(empty: EmptyDomain) -> Self {
+        Inconsistency::EmptyDomain(empty.0)
+    }
 }
 
 #[derive(Default)]
-pub struct CSP {
-    immut: Immut,
-    dyna: Dyna,
+struct Stats {
+    num_propagations: u64,
 }
 
-#[derive(Default)]
-pub struct Dyna {
-    meta: HashMap<IVar, VarMeta>,
-    trail: Trail<Event>,
+/// Theory gathering constraints implemented by a propagator (see [Constraint]).
+///
+/// It handles the expressions of the [Fun::Max], [Fun::AllDifferent], [Fun::Table] and [Fun::Element] functions.
+pub struct CSP {
+    identity: WriterId,
+    constraints: RefVec<CId, Box<dyn Constraint>>,
+    /// For each constraint, a literal that must be true for the constraint to hold.
+    triggers: RefVec<CId, Bound>,
+    /// Schedules the propagation of a constraint when the domain of a variable (of the constraint or of its trigger)
+    /// changes.
+    scheduler: Scheduler<CId>,
+    stats: Stats,
 }
-impl Dyna {
-    fn view_for<'a, 'b>(&'a mut self, cid: CId, model: WModel<'b>) -> CSPView<'a, 'b> {
-        CSPView {
-            owner: cid,
-            csp: self,
-            model,
-        }
-    }
 
-    fn meta(&mut self, var: IVar) -> &mut VarMeta {
-        if !self.meta.contains_key(&var) {
-            self.meta.insert(var, VarMeta::default());
+impl CSP {
+    pub fn new(identity: WriterId) -> CSP {
+        CSP {
+            identity,
+            constraints: Default::default(),
+            triggers: Default::default(),
+            scheduler: Scheduler::new(),
+            stats: Stats::default(),
         }
-        self.meta.get_mut(&var).unwrap()
     }
 
-    pub fn watch(&mut self, cid: CId, var: IVar) {
-        self.meta(var).watches.push(cid);
-        self.trail.push(Event::AddedWatch(var));
+    /// Records a constraint that must hold whenever the `trigger` literal is true.
+    pub fn record(&mut self, trigger: Bound, constraint: Box<dyn Constraint>) -> CId {
+        let cid = self.constraints.push(constraint);
+        let cid2 = self.triggers.push(trigger);
+        debug_assert_eq!(cid, cid2);
+        let mut watched = vec![trigger.variable()];
+        self.constraints[cid].for_each_var(&mut |v| watched.push(v));
+        self.scheduler.add_constraint(cid, watched);
+        cid
     }
-    pub fn unwatch(&mut self, cid: CId, var: IVar) {
-        let watches = &mut self.meta(var).watches;
-        let pos = watches.iter().position(|&c| c == cid).unwrap();
-        watches.remove(pos);
-        self.trail.push(Event::RemovedWatch(var, cid, pos));
+
+    fn propagate_constraint(&mut self, cid: CId, model: &mut DiscreteModel) -> Result<(), Contradiction> {
+        self.stats.num_propagations += 1;
+        let trigger = self.triggers[cid];
+        let constraint = &self.constraints[cid];
+        let cause = self.identity.cause(usize::from(cid) as u32);
+        if model.entails(trigger) {
+            let mut view = CSPView { model, cause };
+            match constraint.propagate(&mut view) {
+                Ok(()) => {}
+                Err(Inconsistency::EmptyDomain(var)) => return Err(Contradiction::EmptyDomain(var)),
+                Err(Inconsistency::Violated) => {
+                    return Err(Contradiction::Explanation(Self::explain_by_bounds(
+                        trigger,
+                        constraint.as_ref(),
+                        model,
+                    )))
+                }
+            }
+        } else if model.entails(!trigger) {
+            // the constraint must not hold: as we do not propagate its negation,
+            // we only check that it is violated once all its variables are bound
+            let mut bound = true;
+            constraint.for_each_var(&mut |v| bound &= model.lb(v) == model.ub(v));
+            let mut explanation = Explanation::new();
+            if bound && !constraint.violation(model, &mut explanation) {
+                return Err(Contradiction::Explanation(Self::explain_by_bounds(
+                    !trigger,
+                    constraint.as_ref(),
+                    model,
+                )));
+            }
+        } else {
+            let mut explanation = Explanation::new();
+            if constraint.violation(model, &mut explanation) {
+                model.domains.set(!trigger, cause)?;
+            }
+        }
+        Ok(())
     }
-    pub fn clear_watches(&mut self, _cid: CId) {
-        todo!()
+
+    /// Explanation of a conflict between the `trigger` literal and the constraint, made of the current bounds
+    /// of all variables of the constraint.
+    fn explain_by_bounds(trigger: Bound, constraint: &dyn Constraint, model: &DiscreteModel) -> Explanation {
+        let mut explanation = Explanation::new();
+        explanation.push(trigger);
+        constraint.for_each_var(&mut |v| {
+            explanation.push(Bound::geq(v, model.lb(v)));
+            explanation.push(Bound::leq(v, model.ub(v)));
+        });
+        explanation
     }
 }
-#[derive(Default)]
-struct Immut {
-    activations: HashMap<Bound, Vec<CId>>,
-    constraints: RefVec<CId, Box<dyn Constraint>>,
-    triggers: RefVec<CId, Bound>,
-}
 
-impl CSP {
-    pub fn record(&mut self, trigger: Bound, constraint: Box<dyn Constraint>) -> CId {
-        let cid = self.immut.constraints.push(constraint);
-        if !self.immut.activations.contains_key(&trigger) {
-            self.immut.activations.insert(trigger, vec![cid]);
-        } else {
-            self.immut.activations.get_mut(&trigger).unwrap().push(cid);
+impl Theory for CSP {
+    fn identity(&self) -> WriterId {
+        self.identity
+    }
+
+    fn bind(
+        &mut self,
+        literal: Bound,
+        expr: ExprHandle,
+        model: &mut Model,
+        _queue: &mut ObsTrail<Binding>,
+    ) -> BindingResult {
+        let expr = model.expressions.get(expr);
+        let var = |i: usize| IVar::try_from(IAtom::try_from(expr.args[i]).expect("not an int")).expect("not a var");
+        match expr.fun {
+            Fun::Max => {
+                let lhs = var(0);
+                let rhs = (1..expr.args.len()).map(var).collect();
+                self.record(literal, Box::new(max::MaxConstraint::new(lhs, rhs)));
+                BindingResult::Enforced
+            }
+            Fun::AllDifferent => {
+                let atoms: Vec<IAtom> = expr
+                    .args
+                    .iter()
+                    .map(|&a| IAtom::try_from(a).expect("not an int"))
+                    .collect();
+                self.record(literal, Box::new(all_different::AllDifferent::new(&atoms)));
+                BindingResult::Enforced
+            }
+            Fun::Table => {
+                let cst = |i: usize| IAtom::try_from(expr.args[i]).expect("not an int").shift;
+                let n = cst(0) as usize;
+                let vars = (1..=n).map(var).collect();
+                let values = (n + 1..expr.args.len()).map(cst).collect();
+                self.record(literal, Box::new(table::TableConstraint::new(vars, values)));
+                BindingResult::Enforced
+            }
+            Fun::Element => {
+                let atoms: Vec<IAtom> = expr
+                    .args
+                    .iter()
+                    .map(|&a| IAtom::try_from(a).expect("not an int"))
+                    .collect();
+                let constraint = element::ElementConstraint::new(atoms[0], atoms[2..].to_vec(), atoms[1]);
+                self.record(literal, Box::new(constraint));
+                BindingResult::Enforced
+            }
+            _ => BindingResult::Unsupported,
         }
-        let cid2 = self.immut.triggers.push(trigger);
-        debug_assert_eq!(cid, cid2);
-        cid
     }
 
-    pub fn trigger(&mut self, lit: Bound, mut model: WModel) -> Update {
-        for &cid in self.immut.activations.get(&lit).unwrap_or(&Vec::new()) {
-            let c = &self.immut.constraints[cid];
-            c.init(self.dyna.view_for(cid, model.dup()))?;
+    fn propagate(&mut self, model: &mut DiscreteModel) -> Result<(), Contradiction> {
+        while let Some(cid) = self.scheduler.pop(model) {
+            self.propagate_constraint(cid, model)?;
         }
         Ok(())
     }
 
-    pub fn propagate(&mut self, changed: IVar, mut model: WModel) -> Update {
-        let watches = self.dyna.meta[&changed].watches.clone(); // todo: inefficient
-        for cid in watches {
-            let c = &self.immut.constraints[cid];
-            let view = self.dyna.view_for(cid, model.dup());
-            c.propagate(changed, view)?;
+    fn explain(&mut self, literal: Bound, context: u32, model: &DiscreteModel, out_explanation: &mut Explanation) {
+        let cid = CId::from(context as usize);
+        let trigger = self.triggers[cid];
+        let constraint = &self.constraints[cid];
+        if literal == !trigger {
+            // the trigger was made false because the constraint is violated
+            let violated = constraint.violation(model, out_explanation);
+            debug_assert!(violated);
+        } else {
+            out_explanation.push(trigger);
+            constraint.explain(literal, model, out_explanation);
         }
-        Ok(())
     }
-}
 
-pub struct CSPView<'a, 'b> {
-    owner: CId,
-    csp: &'a mut Dyna,
-    model: WModel<'b>,
-}
-impl<'a, 'b> CSPView<'a, 'b> {
-    pub fn make_passive(&mut self) {
-        todo!()
+    fn stats(&self) -> Vec<(String, StatValue)> {
+        vec![
+            ("constraints".to_string(), self.constraints.len().into()),
+            ("propagations".to_string(), self.stats.num_propagations.into()),
+        ]
     }
+}
 
-    pub fn watch(&mut self, ivar: IVar) {
-        self.csp.watch(self.owner, ivar);
+impl Backtrack for CSP {
+    fn save_state(&mut self) -> DecLvl {
+        self.scheduler.save_state()
     }
-    pub fn unwatch(&mut self, ivar: IVar) {
-        self.csp.unwatch(self.owner, ivar);
+
+    fn num_saved(&self) -> u32 {
+        self.scheduler.num_saved()
     }
-    pub fn clear_watches(&mut self) {
-        self.csp.clear_watches(self.owner)
+
+    fn restore_last(&mut self) {
+        self.scheduler.restore_last()
     }
+}
 
+/// View of the model given to a propagator, where all modifications are attributed to its constraint.
+pub struct CSPView<'a> {
+    model: &'a mut DiscreteModel,
+    cause: Cause,
+}
+impl<'a> CSPView<'a> {
     pub fn bounds(&self, ivar: IVar) -> (IntCst, IntCst) {
-        self.model.bounds(ivar)
+        self.model.domain_of(ivar)
     }
     pub fn lb(&self, ivar: IVar) -> IntCst {
-        self.model.bounds(ivar).0
+        self.model.lb(ivar)
     }
     pub fn ub(&self, ivar: IVar) -> IntCst {
-        self.model.bounds(ivar).1
+        self.model.ub(ivar)
     }
     pub fn is_instantiated(&self, ivar: IVar) -> bool {
-        let (lb, ub) = self.model.bounds(ivar);
+        let (lb, ub) = self.bounds(ivar);
         lb == ub
     }
 
-    // TODO: use result of set_lower_bound
-    pub fn set_lb(&mut self, ivar: IVar, lb: IntCst) -> Result<bool, UpdateFail> {
-        let (prev_lb, ub) = self.model.bounds(ivar);
-        if lb > ub {
-            Err(UpdateFail::EmptyDom(ivar))
-        } else if prev_lb < lb {
-            self.model.set_lower_bound(ivar, lb, self.owner);
-            Ok(true)
-        } else {
-            Ok(false)
+    /// Sets the lower bound of the variable, returning true if it was modified.
+    pub fn set_lb(&mut self, ivar: IVar, lb: IntCst) -> Result<bool, EmptyDomain> {
+        self.model.set_lb(ivar, lb, self.cause)
+    }
+    /// Sets the upper bound of the variable, returning true if it was modified.
+    pub fn set_ub(&mut self, ivar: IVar, ub: IntCst) -> Result<bool, EmptyDomain> {
+        self.model.set_ub(ivar, ub, self.cause)
+    }
+
+    pub fn model(&self) -> &DiscreteModel {
+        self.model
+    }
+
+    /// Sets the lower bound of an atom, returning true if it was modified.
+    /// Fails with [Inconsistency::Violated] if the atom is a constant that does not satisfy the new bound.
+    pub fn set_atom_lb(&mut self, atom: IAtom, lb: IntCst) -> Result<bool, Inconsistency> {
+        match atom.var {
+            Some(var) => Ok(self.set_lb(var, lb - atom.shift)?),
+            None if lb <= atom.shift => Ok(false),
+            None => Err(Inconsistency::Violated),
         }
     }
-    pub fn set_ub(&mut self, ivar: IVar, ub: IntCst) -> Result<bool, UpdateFail> {
-        let (lb, prev_ub) = self.model.bounds(ivar);
-        if lb > ub {
-            Err(UpdateFail::EmptyDom(ivar))
-        } else if ub < prev_ub {
-            self.model.set_upper_bound(ivar, ub, self.owner);
-            Ok(true)
-        } else {
-            Ok(false)
+    /// Sets the upper bound of an atom, returning true if it was modified.
+    /// Fails with [Inconsistency::Violated] if the atom is a constant that does not satisfy the new bound.
+    pub fn set_atom_ub(&mut self, atom: IAtom, ub: IntCst) -> Result<bool, Inconsistency> {
+        match atom.var {
+            Some(var) => Ok(self.set_ub(var, ub - atom.shift)?),
+            None if atom.shift <= ub => Ok(false),
+            None => Err(Inconsistency::Violated),
+        }
+    }
+}
+
+pub fn atom_bounds(model: &DiscreteModel, atom: IAtom) -> (IntCst, IntCst) {
+    match atom.var {
+        Some(var) => {
+            let (lb, ub) = model.domain_of(var);
+            (lb + atom.shift, ub + atom.shift)
         }
+        None => (atom.shift, atom.shift),
     }
 }
 
+/// Literal stating that `atom >= value`, or `None` if the atom is a constant.
+pub fn atom_geq(atom: IAtom, value: IntCst) -> Option<Bound> {
+    atom.var.map(|var| Bound::geq(var, value - atom.shift))
+}
+
+/// Literal stating that `atom <= value`, or `None` if the atom is a constant.
+pub fn atom_leq(atom: IAtom, value: IntCst) -> Option<Bound> {
+    atom.var.map(|var| Bound::leq(var, value - atom.shift))
+}
+
 pub trait Constraint {
     fn for_each_var(&self, f: &mut dyn FnMut(VarRef));
 
-    fn init(&self, csp: CSPView) -> Update;
+    /// Updates the domains of the variables, assuming the constraint holds.
+    fn propagate(&self, csp: &mut CSPView) -> Update;
 
-    fn propagate(&self, changed: IVar, csp: CSPView) -> Update;
+    /// Explains a `literal` that was set by a previous call to `propagate`, by adding to `out` some literals
+    /// that are true in the `model` (in the state just before `literal` was set) and that imply it with the constraint.
+    fn explain(&self, literal: Bound, model: &DiscreteModel, out: &mut Explanation);
+
+    /// Returns true if the constraint can be proved to be violated in the current domains, in which case
+    /// the literals responsible for the violation are added to `out`.
+    /// A violated constraint must at least be detected when all its variables are bound.
+    fn violation(&self, model: &DiscreteModel, out: &mut Explanation) -> bool;
+}
 
-    fn explain_lb(&self, ivar: IVar, out: &mut Vec<Change>);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atom_bounds() {
+        let mut model = Model::new();
+        let x = model.new_ivar(0, 10, "x");
+        let cause = model.new_write_token().cause(0u32);
+        let mut view = CSPView {
+            model: &mut model.discrete,
+            cause,
+        };
+        assert!(matches!(view.set_atom_lb(x + 2, 4), Ok(true)));
+        assert_eq!(view.bounds(x), (2, 10));
+        assert!(matches!(view.set_atom_ub(x + 2, 1), Err(Inconsistency::EmptyDomain(_))));
+
+        // constants cannot be updated, a bound they do not satisfy is a violation of the constraint
+        assert!(matches!(view.set_atom_lb(3.into(), 3), Ok(false)));
+        assert!(matches!(view.set_atom_ub(3.into(), 5), Ok(false)));
+        assert!(matches!(view.set_atom_lb(3.into(), 4), Err(Inconsistency::Violated)));
+        assert!(matches!(view.set_atom_ub(3.into(), 2), Err(Inconsistency::Violated)));
+    }
 }
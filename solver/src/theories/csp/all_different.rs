@@ -0,0 +1,252 @@
+use crate::theories::csp::{CSPView, Constraint, Inconsistency, Update};
+use aries_model::bounds::{Bound, Relation};
+use aries_model::int_model::{DiscreteModel, Explanation};
+use aries_model::lang::{IAtom, IVar, IntCst, VarRef};
+
+/// Constraint enforcing that all atoms take distinct values.
+///
+/// The propagator enforces bounds consistency, based on Hall intervals: if a set of `n` atoms have their domains
+/// in an interval `[l, u]` of size `n`, then all values of the interval are taken and removed from the domains
+/// of the other atoms.
+pub struct AllDifferent {
+    /// Atoms of the constraint, each of them with a variable.
+    atoms: Vec<(IVar, IntCst)>,
+}
+
+/// Hall interval `[lb, ub]` whose values are all taken by the atoms in `members`.
+struct HallInterval {
+    lb: IntCst,
+    ub: IntCst,
+    members: Vec<usize>,
+}
+
+impl AllDifferent {
+    pub fn new(atoms: &[IAtom]) -> AllDifferent {
+        let atoms = atoms
+            .iter()
+            .map(|a| (a.var.expect("Constant in all-different"), a.shift))
+            .collect();
+        AllDifferent { atoms }
+    }
+
+    fn bounds(&self, model: &DiscreteModel) -> Vec<(IntCst, IntCst)> {
+        self.atoms
+            .iter()
+            .map(|&(var, shift)| {
+                let (lb, ub) = model.domain_of(var);
+                (lb + shift, ub + shift)
+            })
+            .collect()
+    }
+
+    /// Returns an interval `[l, u]`, with `l <= l_max` and `u >= u_min`, that contains the domains of at least
+    /// `u - l + 1 + excess` atoms, not counting the `ignored` one. Only the first `u - l + 1 + excess` atoms are
+    /// returned as members of the interval.
+    fn find_interval(
+        bounds: &[(IntCst, IntCst)],
+        ignored: Option<usize>,
+        l_max: IntCst,
+        u_min: IntCst,
+        excess: i64,
+    ) -> Option<HallInterval> {
+        let mut by_ub: Vec<usize> = (0..bounds.len()).filter(|&i| Some(i) != ignored).collect();
+        by_ub.sort_by_key(|&i| bounds[i].1);
+        let mut lbs: Vec<IntCst> = by_ub.iter().map(|&i| bounds[i].0).filter(|&l| l <= l_max).collect();
+        lbs.sort_unstable();
+        lbs.dedup();
+        for &l in &lbs {
+            let mut members = Vec::new();
+            for &i in &by_ub {
+                let (lb, ub) = bounds[i];
+                if lb < l {
+                    continue;
+                }
+                members.push(i);
+                let required = ub as i64 - l as i64 + 1 + excess;
+                if ub >= u_min && members.len() as i64 >= required {
+                    members.truncate(required.max(0) as usize);
+                    return Some(HallInterval { lb: l, ub, members });
+                }
+            }
+        }
+        None
+    }
+
+    /// Pushes the literals stating that the domain of the atom `i` is contained in `[lb, ub]`.
+    fn push_inclusion(&self, i: usize, lb: IntCst, ub: IntCst, out: &mut Explanation) {
+        let (var, shift) = self.atoms[i];
+        out.push(Bound::geq(var, lb - shift));
+        out.push(Bound::leq(var, ub - shift));
+    }
+}
+
+impl Constraint for AllDifferent {
+    fn for_each_var(&self, f: &mut dyn FnMut(VarRef)) {
+        for &(var, _) in &self.atoms {
+            f(var.into());
+        }
+    }
+
+    fn propagate(&self, csp: &mut CSPView) -> Update {
+        let mut bounds: Vec<(IntCst, IntCst)> = Vec::with_capacity(self.atoms.len());
+        loop {
+            bounds.clear();
+            for &(var, shift) in &self.atoms {
+                let (lb, ub) = csp.bounds(var);
+                bounds.push((lb + shift, ub + shift));
+            }
+            let mut changed = false;
+            let mut by_ub: Vec<usize> = (0..bounds.len()).collect();
+            by_ub.sort_by_key(|&i| bounds[i].1);
+            for i in 0..bounds.len() {
+                let l = bounds[i].0;
+                let mut count: i64 = 0;
+                for &j in &by_ub {
+                    let (lb, u) = bounds[j];
+                    if lb < l {
+                        continue;
+                    }
+                    count += 1;
+                    let size = u as i64 - l as i64 + 1;
+                    if count > size {
+                        // more atoms than values in [l, u]: the last one has an empty domain
+                        let (var, shift) = self.atoms[j];
+                        let emptied = csp.set_lb(var, u + 1 - shift);
+                        debug_assert!(emptied.is_err(), "Not detected as an empty domain");
+                        return emptied.map(|_| ()).map_err(Inconsistency::from);
+                    } else if count == size {
+                        // Hall interval [l, u], remove its values from the other atoms
+                        for (k, &(lb_k, ub_k)) in bounds.iter().enumerate() {
+                            let (var, shift) = self.atoms[k];
+                            if lb_k >= l && ub_k <= u {
+                                continue;
+                            }
+                            if l <= lb_k && lb_k <= u {
+                                changed |= csp.set_lb(var, u + 1 - shift)?;
+                            }
+                            if l <= ub_k && ub_k <= u {
+                                changed |= csp.set_ub(var, l - 1 - shift)?;
+                            }
+                        }
+                    }
+                }
+            }
+            if !changed {
+                return Ok(());
+            }
+        }
+    }
+
+    fn explain(&self, literal: Bound, model: &DiscreteModel, out: &mut Explanation) {
+        let bounds = self.bounds(model);
+        let var = literal.variable();
+        for (i, &(v, shift)) in self.atoms.iter().enumerate() {
+            if VarRef::from(v) != var {
+                continue;
+            }
+            let (lb, ub) = bounds[i];
+            let is_lb = literal.relation() == Relation::GT;
+            let interval = if is_lb {
+                // atom >= k   <=   atom >= l  &  [l, u] is a Hall interval, with u >= k - 1
+                let k = literal.value() + 1 + shift;
+                Self::find_interval(&bounds, Some(i), lb, k - 1, 0)
+            } else {
+                // atom <= k   <=   atom <= u  &  [l, u] is a Hall interval, with l <= k + 1
+                let k = literal.value() + shift;
+                Self::find_interval(&bounds, Some(i), k + 1, ub, 0)
+            };
+            if let Some(interval) = interval {
+                for &j in &interval.members {
+                    self.push_inclusion(j, interval.lb, interval.ub, out);
+                }
+                if is_lb {
+                    out.push(Bound::geq(v, interval.lb - shift));
+                } else {
+                    out.push(Bound::leq(v, interval.ub - shift));
+                }
+                return;
+            }
+            // the other atoms are already in conflict (this may happen when the update was the consequence
+            // of a Hall interval that is only detected as overfull later), any literal can be derived
+            if let Some(interval) = Self::find_interval(&bounds, Some(i), IntCst::MAX, IntCst::MIN, 1) {
+                for &j in &interval.members {
+                    self.push_inclusion(j, interval.lb, interval.ub, out);
+                }
+                return;
+            }
+        }
+        panic!("No explanation found for {:?}", literal);
+    }
+
+    fn violation(&self, model: &DiscreteModel, out: &mut Explanation) -> bool {
+        let bounds = self.bounds(model);
+        match Self::find_interval(&bounds, None, IntCst::MAX, IntCst::MIN, 1) {
+            Some(interval) => {
+                for &i in &interval.members {
+                    self.push_inclusion(i, interval.lb, interval.ub, out);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theories::csp::CSP;
+    use crate::{Contradiction, Theory};
+    use aries_model::int_model::Cause;
+    use aries_model::Model;
+
+    #[test]
+    fn test_hall_intervals() -> Result<(), Contradiction> {
+        let mut model = Model::new();
+        let a = model.new_ivar(1, 2, "a");
+        let b = model.new_ivar(1, 2, "b");
+        let c = model.new_ivar(1, 4, "c");
+        let d = model.new_ivar(0, 5, "d");
+        let e = model.new_ivar(0, 5, "e");
+        let active = model.new_bvar("active").true_lit();
+        let mut csp = CSP::new(model.new_write_token());
+        let alldiff = AllDifferent::new(&[a.into(), b.into(), c + 1, d.into(), e.into()]);
+        csp.record(active, Box::new(alldiff));
+        let model = &mut model.discrete;
+        model.decide(active)?;
+        csp.propagate(model)?;
+
+        // [1, 2] is taken by a and b
+        assert_eq!(model.domain_of(c), (2, 4));
+        assert_eq!(model.domain_of(d), (0, 5));
+        let mut explanation = Explanation::new();
+        csp.explain(Bound::geq(c, 2), 0, model, &mut explanation);
+        assert_eq!(
+            explanation.literals(),
+            &[
+                active,
+                Bound::geq(a, 1),
+                Bound::leq(a, 2),
+                Bound::geq(b, 1),
+                Bound::leq(b, 2),
+                Bound::geq(c, 0),
+            ]
+        );
+
+        // [0, 2] is taken by a, b and d
+        model.set_ub(d, 0, Cause::Decision)?;
+        csp.propagate(model)?;
+        assert_eq!(model.domain_of(e), (3, 5));
+        assert_eq!(model.domain_of(c), (2, 4));
+
+        // [0, 3] is taken by a, b, d and e
+        model.set_ub(e, 3, Cause::Decision)?;
+        csp.propagate(model)?;
+        assert_eq!(model.domain_of(c), (3, 4));
+        let mut explanation = Explanation::new();
+        csp.explain(Bound::geq(c, 3), 0, model, &mut explanation);
+        assert_eq!(explanation.literals().len(), 2 + 4 * 2);
+
+        Ok(())
+    }
+}
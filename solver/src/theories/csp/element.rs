@@ -0,0 +1,337 @@
+use crate::theories::csp::{atom_bounds, atom_geq, atom_leq, CSPView, Constraint, Update};
+use aries_model::bounds::{Bound, Relation};
+use aries_model::int_model::{DiscreteModel, Explanation};
+use aries_model::lang::{IAtom, IntCst, VarRef};
+
+/// Constraint `array[index] = value`, where the first element of the array has index 0.
+///
+/// The propagator removes from the bounds of the index the elements whose domain does not intersect the one of the
+/// value, and restricts the value to the union of the domains of the remaining elements.
+/// When a single element remains, its domain is restricted to the one of the value.
+pub struct ElementConstraint {
+    index: IAtom,
+    array: Vec<IAtom>,
+    value: IAtom,
+}
+
+impl ElementConstraint {
+    pub fn new(index: IAtom, array: Vec<IAtom>, value: IAtom) -> ElementConstraint {
+        assert!(index.var.is_some(), "Constant index in element constraint");
+        ElementConstraint { index, array, value }
+    }
+
+    /// Range of positions of the array allowed by the domain of the index.
+    fn index_range(&self, model: &DiscreteModel) -> std::ops::RangeInclusive<i64> {
+        let (lb, ub) = atom_bounds(model, self.index);
+        (lb as i64).max(0)..=(ub as i64).min(self.array.len() as i64 - 1)
+    }
+
+    /// If the domains of the element `i` and of the value are disjoint, adds the literals responsible
+    /// for it to `out` and returns true.
+    fn explain_disjoint(&self, i: usize, model: &DiscreteModel, out: &mut Explanation) -> bool {
+        let elem = self.array[i];
+        let (elem_lb, elem_ub) = atom_bounds(model, elem);
+        let (value_lb, value_ub) = atom_bounds(model, self.value);
+        let literals = if elem_ub < value_lb {
+            [atom_leq(elem, elem_ub), atom_geq(self.value, elem_ub + 1)]
+        } else if value_ub < elem_lb {
+            [atom_geq(elem, elem_lb), atom_leq(self.value, elem_lb - 1)]
+        } else {
+            return false;
+        };
+        for l in literals.iter().flatten() {
+            out.push(*l);
+        }
+        true
+    }
+
+    /// Adds to `out` the literals stating that all elements in the range of the index (except `excluded`)
+    /// are incompatible with the value. Returns false if it is not the case.
+    fn explain_others_disjoint(&self, excluded: Option<usize>, model: &DiscreteModel, out: &mut Explanation) -> bool {
+        let (index_lb, index_ub) = atom_bounds(model, self.index);
+        out.extend(atom_geq(self.index, index_lb));
+        out.extend(atom_leq(self.index, index_ub));
+        self.index_range(model)
+            .map(|i| i as usize)
+            .filter(|&i| Some(i) != excluded)
+            .all(|i| self.explain_disjoint(i, model, out))
+    }
+
+    /// Explains the bound `index >= k` (or `index <= k` if `is_lb` is false) as a consequence of the elements
+    /// between it and the previous bound of the index being incompatible with the value.
+    /// Returns false if the bound cannot be derived this way.
+    fn explain_index_bound(&self, is_lb: bool, k: IntCst, model: &DiscreteModel, out: &mut Explanation) -> bool {
+        let len = self.array.len() as i64;
+        let step = if is_lb { -1 } else { 1 };
+        let mut i = k as i64 + step;
+        while 0 <= i && i < len && self.explain_disjoint(i as usize, model, out) {
+            i += step;
+        }
+        // the elements out of the array are excluded by the constraint itself
+        if i < 0 || len <= i {
+            return true;
+        }
+        if i == k as i64 + step {
+            // no element excluded, the bound is not derived from the previous one
+            return false;
+        }
+        let previous = if is_lb {
+            atom_geq(self.index, i as IntCst + 1)
+        } else {
+            atom_leq(self.index, i as IntCst - 1)
+        };
+        match previous {
+            Some(l) if model.entails(l) => {
+                out.push(l);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Explains the bound `value >= k` (or `value <= k` if `is_lb` is false) as a consequence of all elements
+    /// in the range of the index either having this bound or being incompatible with the value.
+    /// Returns false if the bound cannot be derived this way.
+    fn explain_value_bound(&self, is_lb: bool, k: IntCst, model: &DiscreteModel, out: &mut Explanation) -> bool {
+        let (index_lb, index_ub) = atom_bounds(model, self.index);
+        out.extend(atom_geq(self.index, index_lb));
+        out.extend(atom_leq(self.index, index_ub));
+        self.index_range(model).all(|i| {
+            let elem = self.array[i as usize];
+            let (elem_lb, elem_ub) = atom_bounds(model, elem);
+            if is_lb && elem_lb >= k {
+                out.extend(atom_geq(elem, k));
+                true
+            } else if !is_lb && elem_ub <= k {
+                out.extend(atom_leq(elem, k));
+                true
+            } else {
+                self.explain_disjoint(i as usize, model, out)
+            }
+        })
+    }
+
+    /// Explains the bound `array[i] >= k` (or `array[i] <= k` if `is_lb` is false) as a consequence of the value
+    /// having this bound and of `i` being the only position compatible with the value.
+    /// Returns false if the bound cannot be derived this way.
+    fn explain_element_bound(
+        &self,
+        i: usize,
+        is_lb: bool,
+        k: IntCst,
+        model: &DiscreteModel,
+        out: &mut Explanation,
+    ) -> bool {
+        let (value_lb, value_ub) = atom_bounds(model, self.value);
+        if is_lb && value_lb >= k {
+            out.extend(atom_geq(self.value, k));
+        } else if !is_lb && value_ub <= k {
+            out.extend(atom_leq(self.value, k));
+        } else {
+            return false;
+        }
+        self.explain_others_disjoint(Some(i), model, out)
+    }
+}
+
+impl Constraint for ElementConstraint {
+    fn for_each_var(&self, f: &mut dyn FnMut(VarRef)) {
+        for atom in std::iter::once(&self.index)
+            .chain(&self.array)
+            .chain(std::iter::once(&self.value))
+        {
+            if let Some(var) = atom.var {
+                f(var.into());
+            }
+        }
+    }
+
+    fn propagate(&self, csp: &mut CSPView) -> Update {
+        loop {
+            let model = csp.model();
+            let (value_lb, value_ub) = atom_bounds(model, self.value);
+            let mut candidates = Vec::new();
+            let mut supported_lb = IntCst::MAX;
+            let mut supported_ub = IntCst::MIN;
+            for i in self.index_range(model) {
+                let (lb, ub) = atom_bounds(model, self.array[i as usize]);
+                if lb <= value_ub && value_lb <= ub {
+                    candidates.push(i as IntCst);
+                    supported_lb = supported_lb.min(lb);
+                    supported_ub = supported_ub.max(ub);
+                }
+            }
+            let mut changed = false;
+            match candidates.as_slice() {
+                [] => {
+                    // no compatible element, the update fails with an empty domain
+                    let (_, index_ub) = atom_bounds(model, self.index);
+                    csp.set_atom_lb(self.index, index_ub + 1)?;
+                }
+                &[single] => {
+                    changed |= csp.set_atom_lb(self.index, single)?;
+                    changed |= csp.set_atom_ub(self.index, single)?;
+                    changed |= csp.set_atom_lb(self.value, supported_lb)?;
+                    changed |= csp.set_atom_ub(self.value, supported_ub)?;
+                    let elem = self.array[single as usize];
+                    let (value_lb, value_ub) = atom_bounds(csp.model(), self.value);
+                    changed |= csp.set_atom_lb(elem, value_lb)?;
+                    changed |= csp.set_atom_ub(elem, value_ub)?;
+                }
+                candidates => {
+                    changed |= csp.set_atom_lb(self.index, candidates[0])?;
+                    changed |= csp.set_atom_ub(self.index, candidates[candidates.len() - 1])?;
+                    changed |= csp.set_atom_lb(self.value, supported_lb)?;
+                    changed |= csp.set_atom_ub(self.value, supported_ub)?;
+                }
+            }
+            if !changed {
+                return Ok(());
+            }
+        }
+    }
+
+    fn explain(&self, literal: Bound, model: &DiscreteModel, out: &mut Explanation) {
+        let var = Some(literal.variable());
+        let is_lb = literal.relation() == Relation::GT;
+        // bound of an atom on `var` that is implied by the literal
+        let implied = |atom: IAtom| {
+            if is_lb {
+                literal.value() + 1 + atom.shift
+            } else {
+                literal.value() + atom.shift
+            }
+        };
+        // the variable may have several roles in the constraint (e.g. both index and value, or several elements
+        // of the array), the literal is explained by the first one from which it can be derived
+        let mut explanation = Explanation::new();
+        if var == self.index.var.map(VarRef::from)
+            && self.explain_index_bound(is_lb, implied(self.index), model, &mut explanation)
+        {
+            out.extend(explanation.literals().iter().copied());
+            return;
+        }
+        explanation.clear();
+        if var == self.value.var.map(VarRef::from)
+            && self.explain_value_bound(is_lb, implied(self.value), model, &mut explanation)
+        {
+            out.extend(explanation.literals().iter().copied());
+            return;
+        }
+        for (i, &elem) in self.array.iter().enumerate() {
+            explanation.clear();
+            if var == elem.var.map(VarRef::from)
+                && self.explain_element_bound(i, is_lb, implied(elem), model, &mut explanation)
+            {
+                out.extend(explanation.literals().iter().copied());
+                return;
+            }
+        }
+        panic!("No explanation found for {:?}", literal);
+    }
+
+    fn violation(&self, model: &DiscreteModel, out: &mut Explanation) -> bool {
+        let (value_lb, value_ub) = atom_bounds(model, self.value);
+        let compatible = self.index_range(model).any(|i| {
+            let (lb, ub) = atom_bounds(model, self.array[i as usize]);
+            lb <= value_ub && value_lb <= ub
+        });
+        if compatible {
+            false
+        } else {
+            let disjoint = self.explain_others_disjoint(None, model, out);
+            debug_assert!(disjoint);
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theories::csp::CSP;
+    use crate::{Contradiction, Theory};
+    use aries_model::int_model::Cause;
+    use aries_model::Model;
+
+    #[test]
+    fn test_element() -> Result<(), Contradiction> {
+        let mut model = Model::new();
+        let index = model.new_ivar(-5, 5, "index");
+        let value = model.new_ivar(0, 10, "value");
+        let x = model.new_ivar(5, 8, "x");
+        let active = model.new_bvar("active").true_lit();
+        let mut csp = CSP::new(model.new_write_token());
+        // [3, x, 9, 4]
+        let array = vec![3.into(), x.into(), 9.into(), 4.into()];
+        csp.record(
+            active,
+            Box::new(ElementConstraint::new(index.into(), array, value.into())),
+        );
+        let model = &mut model.discrete;
+        model.decide(active)?;
+        csp.propagate(model)?;
+        assert_eq!(model.domain_of(index), (0, 3));
+        assert_eq!(model.domain_of(value), (3, 9));
+
+        model.set_lb(value, 5, Cause::Decision)?;
+        csp.propagate(model)?;
+        assert_eq!(model.domain_of(index), (1, 2));
+        let mut explanation = Explanation::new();
+        csp.explain(Bound::geq(index, 1), 0, model, &mut explanation);
+        assert_eq!(explanation.literals(), &[active, Bound::geq(value, 4)]);
+
+        model.set_ub(value, 8, Cause::Decision)?;
+        csp.propagate(model)?;
+        assert_eq!(model.domain_of(index), (1, 1));
+        assert_eq!(model.domain_of(value), (5, 8));
+
+        model.set_ub(x, 6, Cause::Decision)?;
+        csp.propagate(model)?;
+        assert_eq!(model.domain_of(value), (5, 6));
+        let mut explanation = Explanation::new();
+        csp.explain(Bound::leq(value, 6), 0, model, &mut explanation);
+        assert_eq!(
+            explanation.literals(),
+            &[active, Bound::geq(index, 1), Bound::leq(index, 1), Bound::leq(x, 6)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_in_array() -> Result<(), Contradiction> {
+        let mut model = Model::new();
+        let x = model.new_ivar(0, 5, "x");
+        let y = model.new_ivar(0, 10, "y");
+        let value = model.new_ivar(0, 10, "value");
+        let active = model.new_bvar("active").true_lit();
+        let mut csp = CSP::new(model.new_write_token());
+        // x is both the index and the element at position 1: [4, x, 9, y]
+        let array = vec![4.into(), x.into(), 9.into(), y.into()];
+        csp.record(active, Box::new(ElementConstraint::new(x.into(), array, value.into())));
+        let model = &mut model.discrete;
+        model.decide(active)?;
+        csp.propagate(model)?;
+        assert_eq!(model.domain_of(x), (0, 3));
+
+        model.set_lb(value, 5, Cause::Decision)?;
+        csp.propagate(model)?;
+        assert_eq!(model.domain_of(x), (2, 3));
+
+        model.set_ub(y, 4, Cause::Decision)?;
+        csp.propagate(model)?;
+        assert_eq!(model.domain_of(x), (2, 2));
+        assert_eq!(model.domain_of(value), (9, 9));
+
+        // as when minimizing a learnt clause, `x >= 2` is explained after later inferences on `x`,
+        // which are part of the explanation of its exclusion as an element
+        let mut explanation = Explanation::new();
+        csp.explain(Bound::geq(x, 2), 0, model, &mut explanation);
+        assert!(explanation.literals().iter().all(|&l| model.entails(l)));
+        assert_eq!(
+            explanation.literals(),
+            &[active, Bound::leq(x, 2), Bound::geq(value, 3), Bound::geq(value, 5)]
+        );
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@ use aries_collections::set::RefSet;
 use aries_model::lang::*;
 use aries_model::Model;
 use std::cmp::Reverse;
+use std::collections::HashMap;
 
 type EntryId = usize;
 
@@ -81,7 +82,7 @@ impl LazyImplication<'_> {
         let cause = entry.cause.unwrap();
         // add the predecessor of 'p'
         front.put(entry.v, entry.p);
-        let constraint = &self.csp.immut.constraints[cause];
+        let constraint = &self.csp.constraints[cause];
         constraint.for_each_var(&mut |v| self.find_predecessor(v, p, front));
         todo!()
     }
@@ -1,6 +1,10 @@
-use crate::theories::csp::{CSPView, Change, Constraint, Update};
+use crate::theories::csp::{CSPView, Constraint, Update};
+use aries_model::bounds::{Bound, Relation};
+use aries_model::int_model::{DiscreteModel, Explanation};
 use aries_model::lang::{IVar, IntCst, VarRef};
 
+/// Constraint `lhs = max(rhs)`.
+///
 /// Implementation from choco : https://github.com/chocoteam/choco-solver/blob/master/solver/src/main/java/org/chocosolver/solver/constraints/nary/min_max/PropMax.java
 pub struct MaxConstraint {
     lhs: IVar,
@@ -8,7 +12,22 @@ pub struct MaxConstraint {
 }
 
 impl MaxConstraint {
-    pub fn propagate(&self, mut csp: CSPView) -> Update {
+    pub fn new(lhs: IVar, rhs: Vec<IVar>) -> MaxConstraint {
+        assert!(!rhs.is_empty(), "Max of an empty set");
+        debug_assert!(!rhs.contains(&lhs));
+        MaxConstraint { lhs, rhs }
+    }
+}
+
+impl Constraint for MaxConstraint {
+    fn for_each_var(&self, f: &mut dyn FnMut(VarRef)) {
+        f(self.lhs.into());
+        for v in &self.rhs {
+            f(VarRef::from(*v));
+        }
+    }
+
+    fn propagate(&self, csp: &mut CSPView) -> Update {
         let mut filter = true;
         while filter {
             filter = false;
@@ -35,82 +54,121 @@ impl MaxConstraint {
                 }
             }
             if c == self.rhs.len() - 1 {
-                filter = false;
                 let v = self.rhs[idx];
-                csp.set_lb(v, csp.lb(self.lhs))?;
-                csp.set_ub(v, csp.ub(self.lhs))?;
-                if csp.is_instantiated(self.lhs) {
-                    csp.make_passive()
-                }
+                filter |= csp.set_lb(v, csp.lb(self.lhs))?;
             }
         }
 
         Ok(())
     }
-}
 
-impl Constraint for MaxConstraint {
-    fn for_each_var(&self, f: &mut dyn FnMut(VarRef)) {
-        f(self.lhs.into());
-        for v in &self.rhs {
-            f(VarRef::from(*v));
+    fn explain(&self, literal: Bound, model: &DiscreteModel, out: &mut Explanation) {
+        let var = literal.variable();
+        let is_lb = literal.relation() == Relation::GT;
+        if var == self.lhs.into() {
+            if is_lb {
+                // lhs >= k   <=   v >= k, for some v in rhs
+                let k = literal.value() + 1;
+                let v = self.rhs.iter().copied().find(|&v| model.lb(v) >= k);
+                out.push(Bound::geq(v.expect("No support for lower bound"), k));
+            } else {
+                // lhs <= k   <=   v <= k, for all v in rhs
+                let k = literal.value();
+                for &v in &self.rhs {
+                    out.push(Bound::leq(v, k));
+                }
+            }
+        } else if !is_lb {
+            // v <= k   <=   lhs <= k
+            out.push(Bound::leq(self.lhs, literal.value()));
+        } else {
+            // v >= k   <=   lhs >= l  &  w < l, for all other w in rhs, with l >= k the lower bound of lhs
+            // (the literal may be weaker than the one that was propagated)
+            let k = model.lb(self.lhs);
+            debug_assert!(k > literal.value());
+            out.push(Bound::geq(self.lhs, k));
+            for &w in &self.rhs {
+                if VarRef::from(w) != var {
+                    out.push(Bound::leq(w, k - 1));
+                }
+            }
         }
     }
 
-    fn init(&self, mut csp: CSPView) -> Update {
-        csp.watch(self.lhs);
-        for &v in &self.rhs {
-            csp.watch(v);
+    fn violation(&self, model: &DiscreteModel, out: &mut Explanation) -> bool {
+        let (lhs_lb, lhs_ub) = model.domain_of(self.lhs);
+        // some value of rhs is necessarily greater than lhs
+        if let Some(&v) = self.rhs.iter().find(|&&v| model.lb(v) > lhs_ub) {
+            out.push(Bound::leq(self.lhs, lhs_ub));
+            out.push(Bound::geq(v, lhs_ub + 1));
+            return true;
         }
-        self.propagate(csp)
-    }
-
-    fn propagate(&self, _changed: IVar, csp: CSPView) -> Update {
-        self.propagate(csp)
-    }
-
-    fn explain_lb(&self, ivar: IVar, out: &mut Vec<Change>) {
-        if ivar == self.lhs {
-            todo!()
-        } else {
-            out.push(Change::Lb(self.lhs))
+        // all values of rhs are necessarily smaller than lhs
+        if self.rhs.iter().all(|&v| model.ub(v) < lhs_lb) {
+            out.push(Bound::geq(self.lhs, lhs_lb));
+            for &v in &self.rhs {
+                out.push(Bound::leq(v, lhs_lb - 1));
+            }
+            return true;
         }
+        false
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::theories::csp::{UpdateFail, CSP};
-    use aries_model::bounds::Bound;
-    use aries_model::{Model, WriterId};
+    use crate::theories::csp::CSP;
+    use crate::{Contradiction, Theory};
+    use aries_backtrack::Backtrack;
+    use aries_model::int_model::Cause;
+    use aries_model::Model;
 
     #[test]
-    fn test_max() -> Result<(), UpdateFail> {
+    fn test_max() -> Result<(), Contradiction> {
         let mut model = Model::new();
         let act = model.new_bvar("active");
         let a = model.new_ivar(0, 10, "a");
         let b = model.new_ivar(0, 9, "b");
         let c = model.new_ivar(0, 10, "c");
-        let max = MaxConstraint {
-            lhs: a,
-            rhs: vec![b, c],
-        };
-        let writer = &mut model.writer(WriterId::new(0));
-        let act = Bound::geq(act, 1);
-        let mut csp = CSP::default();
+        let max = MaxConstraint::new(a, vec![b, c]);
+        let mut csp = CSP::new(model.new_write_token());
+        let act = act.true_lit();
         csp.record(act, Box::new(max));
-        csp.trigger(act, writer.dup())?;
+        let model = &mut model.discrete;
+        model.decide(act)?;
+        csp.propagate(model)?;
+
+        assert_eq!(model.ub(a), 10);
+        model.set_ub(c, 8, Cause::Decision)?;
+        csp.propagate(model)?;
+        assert_eq!(model.ub(a), 9);
+
+        model.set_ub(a, 7, Cause::Decision)?;
+        csp.propagate(model)?;
+        assert_eq!(model.ub(b), 7);
+        assert_eq!(model.ub(c), 7);
 
-        assert_eq!(writer.bounds(a).1, 10);
-        writer.set_upper_bound(c, 8, 0u32);
-        csp.propagate(c, writer.dup())?;
-        assert_eq!(writer.bounds(a).1, 9);
+        // only c can be the maximum
+        model.save_state();
+        model.set_lb(a, 5, Cause::Decision)?;
+        model.set_ub(b, 4, Cause::Decision)?;
+        csp.propagate(model)?;
+        assert_eq!(model.lb(c), 5);
+        let mut explanation = Explanation::new();
+        csp.explain(Bound::geq(c, 5), 0, model, &mut explanation);
+        assert_eq!(explanation.literals(), &[act, Bound::geq(a, 5), Bound::leq(b, 4)]);
+        // a weaker literal has the same explanation
+        let mut explanation = Explanation::new();
+        csp.explain(Bound::geq(c, 3), 0, model, &mut explanation);
+        assert_eq!(explanation.literals(), &[act, Bound::geq(a, 5), Bound::leq(b, 4)]);
+        model.restore_last();
 
-        writer.set_upper_bound(a, 7, 0u32);
-        csp.propagate(a, writer.dup())?;
-        assert_eq!(writer.bounds(b).1, 7);
-        assert_eq!(writer.bounds(c).1, 7);
+        // a > b and a > c: violation
+        model.set_lb(a, 6, Cause::Decision)?;
+        model.set_ub(b, 5, Cause::Decision)?;
+        model.set_ub(c, 5, Cause::Decision)?;
+        assert!(csp.propagate(model).is_err());
 
         Ok(())
     }
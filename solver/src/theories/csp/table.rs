@@ -0,0 +1,195 @@
+use crate::theories::csp::{CSPView, Constraint, Update};
+use aries_model::bounds::{Bound, Relation};
+use aries_model::int_model::{DiscreteModel, Explanation};
+use aries_model::lang::{IVar, IntCst, VarRef};
+
+/// Constraint stating that the values of a tuple of variables is one of the lines of a table.
+///
+/// The propagator restricts the bounds of each variable to the values it takes in the lines that are still supported,
+/// i.e., whose values are all in the current domains.
+pub struct TableConstraint {
+    vars: Vec<IVar>,
+    /// Linear representation of the lines of the table, each of them with one value per variable.
+    values: Vec<IntCst>,
+}
+
+impl TableConstraint {
+    pub fn new(vars: Vec<IVar>, values: Vec<IntCst>) -> TableConstraint {
+        assert!(!vars.is_empty() && values.chunks_exact(vars.len()).remainder().is_empty());
+        TableConstraint { vars, values }
+    }
+
+    fn lines(&self) -> impl Iterator<Item = &[IntCst]> {
+        self.values.chunks(self.vars.len())
+    }
+
+    /// Returns the first column whose current domain does not contain the value of the line, if any.
+    /// The `last` column, if given, is only considered when no other column excludes the line.
+    fn excluding_column(&self, line: &[IntCst], bounds: &[(IntCst, IntCst)], last: Option<usize>) -> Option<usize> {
+        let excludes = |col: usize| line[col] < bounds[col].0 || bounds[col].1 < line[col];
+        (0..self.vars.len())
+            .filter(|&col| Some(col) != last)
+            .find(|&col| excludes(col))
+            .or_else(|| last.filter(|&col| excludes(col)))
+    }
+
+    fn bounds(&self, model: &DiscreteModel) -> Vec<(IntCst, IntCst)> {
+        self.vars.iter().map(|&v| model.domain_of(v)).collect()
+    }
+
+    /// Explains why all lines selected by the `relevant` filter are not supported in the current domains,
+    /// preferably without relying on the domain of the `last` column.
+    /// For each variable, at most one literal on its lower bound and one on its upper bound are added to `out`.
+    fn explain_unsupported(
+        &self,
+        model: &DiscreteModel,
+        relevant: impl Fn(&[IntCst]) -> bool,
+        last: Option<usize>,
+        out: &mut Explanation,
+    ) {
+        let bounds = self.bounds(model);
+        // for each variable, the lower (resp. upper) bound needed to exclude the lines
+        let mut needed_lbs: Vec<Option<IntCst>> = vec![None; self.vars.len()];
+        let mut needed_ubs: Vec<Option<IntCst>> = vec![None; self.vars.len()];
+        for line in self.lines().filter(|l| relevant(l)) {
+            let col = self
+                .excluding_column(line, &bounds, last)
+                .expect("Explaining a supported line");
+            if line[col] < bounds[col].0 {
+                needed_lbs[col] = needed_lbs[col].max(Some(line[col] + 1));
+            } else {
+                needed_ubs[col] = Some(needed_ubs[col].map_or(line[col] - 1, |ub| ub.min(line[col] - 1)));
+            }
+        }
+        for (col, &var) in self.vars.iter().enumerate() {
+            if let Some(lb) = needed_lbs[col] {
+                out.push(Bound::geq(var, lb));
+            }
+            if let Some(ub) = needed_ubs[col] {
+                out.push(Bound::leq(var, ub));
+            }
+        }
+    }
+}
+
+impl Constraint for TableConstraint {
+    fn for_each_var(&self, f: &mut dyn FnMut(VarRef)) {
+        for &v in &self.vars {
+            f(v.into());
+        }
+    }
+
+    fn propagate(&self, csp: &mut CSPView) -> Update {
+        let n = self.vars.len();
+        loop {
+            let bounds: Vec<(IntCst, IntCst)> = self.vars.iter().map(|&v| csp.bounds(v)).collect();
+            // for each variable, smallest and largest values in the supported lines
+            let mut supported: Vec<(IntCst, IntCst)> = vec![(IntCst::MAX, IntCst::MIN); n];
+            for line in self.lines() {
+                if self.excluding_column(line, &bounds, None).is_none() {
+                    for col in 0..n {
+                        supported[col].0 = supported[col].0.min(line[col]);
+                        supported[col].1 = supported[col].1.max(line[col]);
+                    }
+                }
+            }
+            let mut changed = false;
+            for col in 0..n {
+                let (lb, ub) = supported[col];
+                if lb > ub {
+                    // no supported line, the update fails with an empty domain
+                    csp.set_lb(self.vars[col], bounds[col].1 + 1)?;
+                }
+                changed |= csp.set_lb(self.vars[col], lb)?;
+                changed |= csp.set_ub(self.vars[col], ub)?;
+            }
+            if !changed {
+                return Ok(());
+            }
+        }
+    }
+
+    fn explain(&self, literal: Bound, model: &DiscreteModel, out: &mut Explanation) {
+        let var = literal.variable();
+        let col = self
+            .vars
+            .iter()
+            .position(|&v| VarRef::from(v) == var)
+            .expect("Not a variable of the constraint");
+        if literal.relation() == Relation::GT {
+            // var >= k   <=   all lines with a smaller value are excluded
+            let k = literal.value() + 1;
+            self.explain_unsupported(model, |line| line[col] < k, Some(col), out);
+        } else {
+            // var <= k   <=   all lines with a greater value are excluded
+            let k = literal.value();
+            self.explain_unsupported(model, |line| line[col] > k, Some(col), out);
+        }
+    }
+
+    fn violation(&self, model: &DiscreteModel, out: &mut Explanation) -> bool {
+        let bounds = self.bounds(model);
+        if self
+            .lines()
+            .all(|line| self.excluding_column(line, &bounds, None).is_some())
+        {
+            self.explain_unsupported(model, |_| true, None, out);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theories::csp::CSP;
+    use crate::{Contradiction, Theory};
+    use aries_model::int_model::Cause;
+    use aries_model::Model;
+
+    #[test]
+    fn test_table() -> Result<(), Contradiction> {
+        let mut model = Model::new();
+        let x = model.new_ivar(0, 10, "x");
+        let y = model.new_ivar(0, 10, "y");
+        let active = model.new_bvar("active").true_lit();
+        let mut csp = CSP::new(model.new_write_token());
+        #[rustfmt::skip]
+        let table = TableConstraint::new(vec![x, y], vec![
+            1, 5,
+            2, 3,
+            2, 7,
+            4, 1,
+        ]);
+        csp.record(active, Box::new(table));
+        let model = &mut model.discrete;
+        model.decide(active)?;
+        csp.propagate(model)?;
+        assert_eq!(model.domain_of(x), (1, 4));
+        assert_eq!(model.domain_of(y), (1, 7));
+
+        model.set_ub(y, 6, Cause::Decision)?;
+        model.set_lb(y, 2, Cause::Decision)?;
+        csp.propagate(model)?;
+        assert_eq!(model.domain_of(x), (1, 2));
+        assert_eq!(model.domain_of(y), (3, 5));
+
+        // x <= 2 because the line (4, 1) is excluded by y >= 2
+        let mut explanation = Explanation::new();
+        csp.explain(Bound::leq(x, 2), 0, model, &mut explanation);
+        assert_eq!(explanation.literals(), &[active, Bound::geq(y, 2)]);
+
+        // y >= 3 because the line (4, 1) is excluded by x <= 2
+        let mut explanation = Explanation::new();
+        csp.explain(Bound::geq(y, 3), 0, model, &mut explanation);
+        assert_eq!(explanation.literals(), &[active, Bound::leq(x, 3)]);
+
+        model.set_lb(x, 2, Cause::Decision)?;
+        csp.propagate(model)?;
+        assert_eq!(model.domain_of(y), (3, 3));
+        model.set_ub(y, 2, Cause::Decision).unwrap_err();
+        Ok(())
+    }
+}
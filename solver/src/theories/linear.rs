@@ -0,0 +1,265 @@
+//! Bounds propagation for linear constraints `sum(f_i * x_i) <= ub` (see [Fun::Linear]).
+
+use crate::solver::stats::StatValue;
+use crate::solver::{Binding, BindingResult};
+use crate::theories::scheduler::Scheduler;
+use crate::{Contradiction, Theory};
+use aries_backtrack::{Backtrack, DecLvl, ObsTrail};
+use aries_model::bounds::Bound;
+use aries_model::expressions::ExprHandle;
+use aries_model::int_model::{DiscreteModel, Explanation};
+use aries_model::lang::{Fun, IntCst, LinearLeq, VarRef};
+use aries_model::{Model, WriterId};
+use std::convert::TryFrom;
+
+/// A linear constraint `sum(factor_i * var_i) <= upper_bound` that must hold whenever `enabler` is true.
+struct LinearConstraint {
+    enabler: Bound,
+    terms: Vec<(IntCst, VarRef)>,
+    upper_bound: i64,
+}
+
+impl LinearConstraint {
+    /// Literal giving the minimal value of the term `factor * var` in the current domain of `var`.
+    fn min_literal(factor: IntCst, var: VarRef, model: &DiscreteModel) -> Bound {
+        if factor > 0 {
+            Bound::geq(var, model.lb(var))
+        } else {
+            Bound::leq(var, model.ub(var))
+        }
+    }
+
+    fn min_value(factor: IntCst, var: VarRef, model: &DiscreteModel) -> i64 {
+        if factor > 0 {
+            factor as i64 * model.lb(var) as i64
+        } else {
+            factor as i64 * model.ub(var) as i64
+        }
+    }
+
+    /// Minimal value of the sum in the current domains.
+    fn min_sum(&self, model: &DiscreteModel) -> i64 {
+        self.terms
+            .iter()
+            .map(|&(factor, var)| Self::min_value(factor, var, model))
+            .sum()
+    }
+}
+
+#[derive(Default)]
+struct Stats {
+    num_propagations: u64,
+    num_bound_updates: u64,
+}
+
+/// Theory handling linear constraints (expressions of [Fun::Linear]) by bounds propagation.
+///
+/// Each constraint `l <=> sum(f_i * x_i) <= ub` is enforced as the two constraints `l => sum(f_i * x_i) <= ub` and
+/// `!l => sum(-f_i * x_i) <= -ub - 1`. Whenever the enabling literal of one of them holds, the bounds of its variables
+/// are tightened so that the minimal value of the sum remains below its upper bound. When the minimal value
+/// exceeds the upper bound, the enabling literal is made false.
+pub struct LinearTheory {
+    identity: WriterId,
+    constraints: Vec<LinearConstraint>,
+    /// Schedules the propagation of a constraint when the domain of a variable (appearing in the sum or
+    /// in the enabler) changes.
+    scheduler: Scheduler<usize>,
+    stats: Stats,
+}
+
+impl LinearTheory {
+    pub fn new(identity: WriterId) -> LinearTheory {
+        LinearTheory {
+            identity,
+            constraints: Vec::new(),
+            scheduler: Scheduler::new(),
+            stats: Stats::default(),
+        }
+    }
+
+    /// Records the constraint `enabler => leq`.
+    pub fn add_constraint(&mut self, enabler: Bound, leq: &LinearLeq) {
+        let id = self.constraints.len();
+        let terms: Vec<(IntCst, VarRef)> = leq.terms.iter().map(|t| (t.factor, t.var.into())).collect();
+        let watched = terms.iter().map(|t| t.1).chain(std::iter::once(enabler.variable()));
+        self.scheduler.add_constraint(id, watched);
+        self.constraints.push(LinearConstraint {
+            enabler,
+            terms,
+            upper_bound: leq.upper_bound as i64,
+        });
+    }
+
+    fn propagate_constraint(&mut self, id: usize, model: &mut DiscreteModel) -> Result<(), Contradiction> {
+        self.stats.num_propagations += 1;
+        let c = &self.constraints[id];
+        let cause = self.identity.cause(id as u32);
+        if model.entails(!c.enabler) {
+            return Ok(());
+        }
+        let min_sum = c.min_sum(model);
+        if !model.entails(c.enabler) {
+            if min_sum > c.upper_bound {
+                // the constraint cannot hold, disable it
+                model.domains.set(!c.enabler, cause)?;
+                self.stats.num_bound_updates += 1;
+            }
+            return Ok(());
+        }
+        for &(factor, var) in &c.terms {
+            // maximal value of this term for the sum to remain below the upper bound
+            let slack = c.upper_bound - (min_sum - LinearConstraint::min_value(factor, var, model));
+            let factor = factor as i64;
+            let updated = if factor > 0 {
+                let ub = slack.div_euclid(factor).max(IntCst::MIN as i64) as IntCst;
+                model.set_ub(var, ub, cause)?
+            } else {
+                let lb = (-slack.div_euclid(-factor)).min(IntCst::MAX as i64) as IntCst;
+                model.set_lb(var, lb, cause)?
+            };
+            if updated {
+                self.stats.num_bound_updates += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Theory for LinearTheory {
+    fn identity(&self) -> WriterId {
+        self.identity
+    }
+
+    fn bind(
+        &mut self,
+        literal: Bound,
+        expr: ExprHandle,
+        model: &mut Model,
+        _queue: &mut ObsTrail<Binding>,
+    ) -> BindingResult {
+        let expr = model.expressions.get(expr);
+        match expr.fun {
+            Fun::Linear => {
+                let leq = LinearLeq::try_from(expr).expect("Malformed linear expression");
+                // !literal => sum(f_i * x_i) > ub   <=>  sum(-f_i * x_i) <= -ub - 1
+                let mut negated = leq.clone();
+                for t in &mut negated.terms {
+                    t.factor = -t.factor;
+                }
+                negated.upper_bound = -leq.upper_bound - 1;
+                self.add_constraint(literal, &leq);
+                self.add_constraint(!literal, &negated);
+                BindingResult::Enforced
+            }
+            _ => BindingResult::Unsupported,
+        }
+    }
+
+    fn propagate(&mut self, model: &mut DiscreteModel) -> Result<(), Contradiction> {
+        while let Some(id) = self.scheduler.pop(model) {
+            self.propagate_constraint(id, model)?;
+        }
+        Ok(())
+    }
+
+    fn explain(&mut self, literal: Bound, context: u32, model: &DiscreteModel, out_explanation: &mut Explanation) {
+        let c = &self.constraints[context as usize];
+        if literal.variable() == c.enabler.variable() {
+            // the enabler was made false because the minimal value of the sum exceeds the upper bound
+            for &(factor, var) in &c.terms {
+                out_explanation.push(LinearConstraint::min_literal(factor, var, model));
+            }
+        } else {
+            // bound on a variable of the sum, implied by the enabler and the minimal values of the other terms
+            out_explanation.push(c.enabler);
+            for &(factor, var) in &c.terms {
+                if var != literal.variable() {
+                    out_explanation.push(LinearConstraint::min_literal(factor, var, model));
+                }
+            }
+        }
+    }
+
+    fn stats(&self) -> Vec<(String, StatValue)> {
+        vec![
+            ("constraints".to_string(), self.constraints.len().into()),
+            ("propagations".to_string(), self.stats.num_propagations.into()),
+            ("bound_updates".to_string(), self.stats.num_bound_updates.into()),
+        ]
+    }
+}
+
+impl Backtrack for LinearTheory {
+    fn save_state(&mut self) -> DecLvl {
+        self.scheduler.save_state()
+    }
+
+    fn num_saved(&self) -> u32 {
+        self.scheduler.num_saved()
+    }
+
+    fn restore_last(&mut self) {
+        self.scheduler.restore_last()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aries_model::int_model::Cause;
+    use aries_model::lang::{LinearSum, LinearTerm};
+
+    #[test]
+    fn test_bounds_propagation() {
+        let mut model = Model::new();
+        let x = model.new_ivar(0, 10, "x");
+        let y = model.new_ivar(0, 10, "y");
+        let z = model.new_ivar(0, 10, "z");
+        let active = model.new_bvar("active");
+        let mut theory = LinearTheory::new(model.new_write_token());
+
+        // active => 2x + 3y - z <= 4
+        let leq = LinearLeq::from_sums(LinearSum::from(x) * 2 + LinearSum::from(y) * 3 - z, 4.into());
+        assert_eq!(leq.terms[2], LinearTerm { factor: -1, var: z });
+        theory.add_constraint(active.true_lit(), &leq);
+        theory.propagate(&mut model.discrete).unwrap();
+        assert_eq!(model.discrete.domain_of(x), (0, 10));
+
+        model.discrete.set_lb(z, 2, Cause::Decision).unwrap();
+        model.discrete.decide(active.true_lit()).unwrap();
+        theory.propagate(&mut model.discrete).unwrap();
+        // 2x <= 4 + 10 => x <= 7,   3y <= 4 + 10 => y <= 4
+        assert_eq!(model.discrete.domain_of(x), (0, 7));
+        assert_eq!(model.discrete.domain_of(y), (0, 4));
+        assert_eq!(model.discrete.domain_of(z), (2, 10));
+
+        model.discrete.set_lb(y, 2, Cause::Decision).unwrap();
+        theory.propagate(&mut model.discrete).unwrap();
+        // 2x <= 4 + 10 - 6 => x <= 4,   -z <= 4 - 6  =>  z >= 2
+        assert_eq!(model.discrete.domain_of(x), (0, 4));
+        assert_eq!(model.discrete.domain_of(z), (2, 10));
+
+        let mut explanation = Explanation::new();
+        theory.explain(Bound::leq(x, 4), 0, &model.discrete, &mut explanation);
+        assert_eq!(
+            explanation.literals(),
+            &[active.true_lit(), Bound::geq(y, 2), Bound::leq(z, 10)]
+        );
+
+        // with x >= 5, the constraint is violated and `active` should be false
+        let mut model2 = Model::new();
+        let x = model2.new_ivar(5, 10, "x");
+        let y = model2.new_ivar(0, 10, "y");
+        let active = model2.new_bvar("active");
+        let mut theory = LinearTheory::new(model2.new_write_token());
+        theory.add_constraint(
+            active.true_lit(),
+            &LinearLeq::from_sums(LinearSum::from(x) + y, 4.into()),
+        );
+        theory.propagate(&mut model2.discrete).unwrap();
+        assert_eq!(model2.discrete.value(active.true_lit()), Some(false));
+        let mut explanation = Explanation::new();
+        theory.explain(active.false_lit(), 0, &model2.discrete, &mut explanation);
+        assert_eq!(explanation.literals(), &[Bound::geq(x, 5), Bound::geq(y, 0)]);
+    }
+}
@@ -1 +1,3 @@
 pub mod csp;
+pub mod linear;
+pub mod scheduler;
@@ -0,0 +1,137 @@
+//! Scheduling of the constraints of a theory, propagated whenever the domain of one of their variables changes.
+
+use aries_backtrack::{Backtrack, DecLvl, ObsTrailCursor};
+use aries_collections::ref_store::Ref;
+use aries_model::int_model::domains::Event;
+use aries_model::int_model::DiscreteModel;
+use aries_model::lang::VarRef;
+use std::collections::{HashMap, VecDeque};
+
+/// Queue of the constraints (identified by `C`) that need to be propagated.
+///
+/// A constraint is scheduled when it is added and whenever an event affects one of the variables it watches.
+/// Events are processed by batches: all pending events are read before the constraints they scheduled are returned.
+///
+/// The scheduler has no state to restore on backtrack: the [Backtrack] implementation only counts the saved states,
+/// so that a theory can delegate its own implementation to it.
+pub struct Scheduler<C> {
+    watches: HashMap<VarRef, Vec<C>>,
+    /// Constraints that have not been propagated since their addition.
+    new_constraints: Vec<C>,
+    model_events: ObsTrailCursor<Event>,
+    /// Constraints scheduled by the last batch of events.
+    queue: VecDeque<C>,
+    /// For each constraint, true if it is in the queue.
+    queued: Vec<bool>,
+    num_saved: u32,
+}
+
+impl<C: Ref> Scheduler<C> {
+    pub fn new() -> Self {
+        Scheduler {
+            watches: HashMap::new(),
+            new_constraints: Vec::new(),
+            model_events: ObsTrailCursor::new(),
+            queue: VecDeque::new(),
+            queued: Vec::new(),
+            num_saved: 0,
+        }
+    }
+
+    /// Adds a constraint that must be propagated whenever the domain of one of the `watched` variables changes.
+    /// The constraint is scheduled for its first propagation.
+    pub fn add_constraint(&mut self, constraint: C, watched: impl IntoIterator<Item = VarRef>) {
+        for var in watched {
+            self.watches.entry(var).or_default().push(constraint);
+        }
+        let index: usize = constraint.into();
+        if self.queued.len() <= index {
+            self.queued.resize(index + 1, false);
+        }
+        self.new_constraints.push(constraint);
+    }
+
+    /// Returns the next constraint to propagate, or `None` if no event affected a constraint since it was last returned.
+    pub fn pop(&mut self, model: &DiscreteModel) -> Option<C> {
+        if let Some(constraint) = self.new_constraints.pop() {
+            return Some(constraint);
+        }
+        if self.queue.is_empty() {
+            // read all pending events, which may result in new events once the scheduled constraints are propagated
+            while let Some(ev) = self.model_events.pop(model.trail()) {
+                if let Some(watchers) = self.watches.get(&ev.affected_bound.variable()) {
+                    for &constraint in watchers {
+                        let index: usize = constraint.into();
+                        if !self.queued[index] {
+                            self.queued[index] = true;
+                            self.queue.push_back(constraint);
+                        }
+                    }
+                }
+            }
+        }
+        let constraint = self.queue.pop_front()?;
+        self.queued[constraint.into()] = false;
+        Some(constraint)
+    }
+}
+
+impl<C: Ref> Default for Scheduler<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> Backtrack for Scheduler<C> {
+    fn save_state(&mut self) -> DecLvl {
+        self.num_saved += 1;
+        DecLvl::from(self.num_saved)
+    }
+
+    fn num_saved(&self) -> u32 {
+        self.num_saved
+    }
+
+    fn restore_last(&mut self) {
+        self.num_saved -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aries_model::int_model::Cause;
+    use aries_model::Model;
+
+    #[test]
+    fn test_scheduling() {
+        let mut model = Model::new();
+        let x = model.new_ivar(0, 10, "x");
+        let y = model.new_ivar(0, 10, "y");
+        let z = model.new_ivar(0, 10, "z");
+        let model = &mut model.discrete;
+        let mut scheduler: Scheduler<usize> = Scheduler::new();
+        scheduler.add_constraint(0, vec![x.into(), y.into()]);
+        scheduler.add_constraint(1, vec![y.into(), z.into()]);
+        let mut pop_all = |model: &DiscreteModel| {
+            let mut constraints = Vec::new();
+            while let Some(c) = scheduler.pop(model) {
+                constraints.push(c);
+            }
+            constraints.sort_unstable();
+            constraints
+        };
+
+        // new constraints are scheduled for their first propagation
+        assert_eq!(pop_all(model), vec![0, 1]);
+        assert_eq!(pop_all(model), Vec::<usize>::new());
+
+        // a constraint is scheduled once for several events on its variables
+        model.set_lb(x, 2, Cause::Decision).unwrap();
+        model.set_lb(y, 2, Cause::Decision).unwrap();
+        model.set_ub(y, 8, Cause::Decision).unwrap();
+        assert_eq!(pop_all(model), vec![0, 1]);
+        model.set_ub(z, 8, Cause::Decision).unwrap();
+        assert_eq!(pop_all(model), vec![1]);
+    }
+}
@@ -708,6 +708,7 @@ impl IncSTN {
 
 use aries_backtrack::{DecLvl, ObsTrail, ObsTrailCursor, Trail};
 use aries_model::lang::{Fun, IAtom, IVar, IntCst, VarRef};
+use aries_solver::solver::stats::StatValue;
 use aries_solver::solver::{Binding, BindingResult};
 
 use aries_solver::{Contradiction, Theory};
@@ -780,6 +781,15 @@ impl Theory for IncSTN {
         self.explain_event(event, edge_id, model, out_explanation);
     }
 
+    fn stats(&self) -> Vec<(String, StatValue)> {
+        vec![
+            ("nodes".to_string(), self.num_nodes().into()),
+            ("constraints".to_string(), self.constraints.constraints.len().into()),
+            ("propagations".to_string(), self.stats.num_propagations.into()),
+            ("domain_updates".to_string(), self.stats.distance_updates.into()),
+        ]
+    }
+
     fn print_stats(&self) {
         self.print_stats()
     }